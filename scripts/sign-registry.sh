@@ -0,0 +1,32 @@
+#!/bin/bash
+# Sign every curated manifest and tool definition with the registry key
+#
+# Usage: ./scripts/sign-registry.sh [minisign secret key]
+#   (defaults to minisign's own default, ~/.minisign/minisign.key)
+#
+# Writes a detached <file>.minisig next to each bottles/*/manifest.json and
+# tools/*.json. The matching public key must be in signature::TRUSTED_KEYS.
+
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+
+cd "$PROJECT_DIR"
+
+if ! command -v minisign >/dev/null; then
+    echo "ERROR: minisign not found. Install it (brew install minisign)." >&2
+    exit 1
+fi
+
+KEY_ARGS=()
+if [ -n "$1" ]; then
+    KEY_ARGS=(-s "$1")
+fi
+
+FILES=(bottles/*/manifest.json tools/*.json)
+
+# One invocation so minisign asks for the key password once
+minisign -S "${KEY_ARGS[@]}" -m "${FILES[@]}"
+
+echo "==> Signed ${#FILES[@]} file(s). Commit the .minisig files alongside them."
@@ -0,0 +1,143 @@
+//! Local cache of fetched registry documents (~/.bottle/cache)
+//!
+//! Every manifest and tool definition fetched from the registry is stored here with
+//! its ETag/Last-Modified so later fetches can revalidate, fall back when the network
+//! is down, or be served entirely from disk with `--offline`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Process-wide offline switch, set once from the `--offline` flag
+/// AIDEV-NOTE: A global rather than a parameter because every fetch path (commands,
+/// diff, status, list) would otherwise need it threaded through.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable offline mode
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Check if offline mode is enabled
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Validators and timestamp stored next to each cached document
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheMeta {
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// A document read back from the cache
+#[derive(Debug)]
+pub struct CachedDocument {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// When the document was last fetched or revalidated
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CachedDocument {
+    /// Human-readable age, e.g. "3 days ago"
+    pub fn age(&self) -> String {
+        let age = Utc::now() - self.fetched_at;
+        match (age.num_days(), age.num_hours(), age.num_minutes()) {
+            (d, _, _) if d > 0 => format!("{} day(s) ago", d),
+            (_, h, _) if h > 0 => format!("{} hour(s) ago", h),
+            (_, _, m) if m > 0 => format!("{} minute(s) ago", m),
+            _ => "just now".to_string(),
+        }
+    }
+}
+
+/// Get the cache directory
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".bottle").join("cache"))
+}
+
+/// Paths for a document and its metadata. `key` is the registry-relative path,
+/// e.g. "bottles/stable/manifest.json"; None for a key that could leave the cache
+/// directory (absolute, or with `..`).
+fn paths(key: &str) -> Option<(PathBuf, PathBuf)> {
+    let inside = Path::new(key)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)));
+    if !inside {
+        return None;
+    }
+    let dir = cache_dir()?;
+    let body = dir.join(key);
+    let meta = dir.join(format!("{}.meta", key));
+    Some((body, meta))
+}
+
+/// Load a cached document
+pub fn load(key: &str) -> Option<CachedDocument> {
+    let (body_path, meta_path) = paths(key)?;
+    let body = std::fs::read_to_string(body_path).ok()?;
+    let meta: CacheMeta = std::fs::read_to_string(meta_path)
+        .ok()
+        .and_then(|m| serde_json::from_str(&m).ok())?;
+
+    Some(CachedDocument {
+        body,
+        etag: meta.etag,
+        last_modified: meta.last_modified,
+        fetched_at: meta.fetched_at,
+    })
+}
+
+/// Store a freshly fetched document with its validators
+pub fn store(
+    key: &str,
+    body: &str,
+    etag: Option<String>,
+    last_modified: Option<String>,
+) -> std::io::Result<()> {
+    let (body_path, meta_path) = paths(key).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("No cache path for '{}'", key),
+        )
+    })?;
+
+    if let Some(parent) = body_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let meta = CacheMeta {
+        etag,
+        last_modified,
+        fetched_at: Utc::now(),
+    };
+    std::fs::write(&body_path, body)?;
+    std::fs::write(&meta_path, serde_json::to_string_pretty(&meta)?)
+}
+
+/// Mark a cached document as revalidated (server answered 304)
+pub fn touch(doc: &CachedDocument, key: &str) -> std::io::Result<()> {
+    store(key, &doc.body, doc.etag.clone(), doc.last_modified.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_stay_inside_the_cache() {
+        let (body, meta) = paths("tools/ba.json").unwrap();
+        assert!(body.starts_with(cache_dir().unwrap()));
+        assert!(meta.ends_with("tools/ba.json.meta"));
+
+        assert!(paths("tools/../../x.json").is_none());
+        assert!(paths("bottles/../../../x/manifest.json").is_none());
+        assert!(paths("/etc/passwd").is_none());
+    }
+}
@@ -1,12 +1,17 @@
+use super::common::load_project;
 use crate::error::{BottleError, Result};
 use crate::manifest::state::BottleState;
 
-/// Output AGENTS.md snippet for the active bottle.
+/// Output AGENTS.md snippet for the active bottle (inside a project, the project's).
 /// Used by AI agents to get the snippet content they should inject.
 pub fn run() -> Result<()> {
-    let state = BottleState::load().ok_or(BottleError::NoBottleInstalled)?;
+    let state = match load_project()? {
+        Some((_, manifest)) => BottleState::load_for(&manifest.name),
+        None => BottleState::load(),
+    }
+    .ok_or(BottleError::NoBottleInstalled)?;
 
-    match BottleState::load_snippet() {
+    match BottleState::load_snippet_for(&state.bottle) {
         Some(snippet) => {
             print!("{}", snippet);
             Ok(())
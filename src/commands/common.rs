@@ -1,6 +1,22 @@
+use super::transaction::Transaction;
 use crate::error::{BottleError, Result};
-use crate::fetch::fetch_bottle_manifest;
-use crate::manifest::bottle::BottleManifest;
+use crate::fetch::{
+    fetch_bottle_index, fetch_bottle_manifest, fetch_latest_version, fetch_tool_definition,
+    fetch_versions,
+};
+use crate::install::{parallel, requires};
+use crate::manifest::bottle::{BottleManifest, BrewPolicy};
+use crate::manifest::channel::Channel;
+use crate::manifest::history::{HistoryEntry, Operation};
+use crate::manifest::project::{lock_path_for, Lockfile, Project};
+use crate::manifest::state::{BottleState, ToolState};
+use crate::manifest::tool::Requirement;
+use crate::signature;
+use crate::source::{Fetched, Source};
+use crate::ui::{self, OutputFormat};
+use crate::version::{self, Spec};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -8,7 +24,7 @@ use std::time::Duration;
 /// Marketplace identifier for plugins
 pub const MARKETPLACE: &str = "open-horizon-labs";
 
-/// Fetch manifest from explicit path, bespoke location, or GitHub
+/// Fetch manifest from explicit path, bespoke location, or the registry
 pub fn fetch_or_load_manifest(
     bottle: &str,
     manifest_path: Option<&std::path::Path>,
@@ -22,48 +38,413 @@ pub fn fetch_or_load_manifest(
                 e
             ))
         })?;
-        return serde_json::from_str(&contents).map_err(BottleError::ParseError);
+        return parse_bespoke_manifest(path, &contents);
     }
 
     // Check bespoke first (~/.bottle/bottles/<name>/)
-    if let Some(home) = dirs::home_dir() {
-        let bespoke_path = home
-            .join(".bottle")
-            .join("bottles")
-            .join(bottle)
-            .join("manifest.json");
-
-        if bespoke_path.exists() {
-            let contents = fs::read_to_string(&bespoke_path).map_err(|e| {
-                BottleError::Other(format!("Failed to read bespoke manifest: {}", e))
-            })?;
-            return serde_json::from_str(&contents).map_err(BottleError::ParseError);
-        }
+    if let Some(bespoke_path) = bespoke_manifest_path(bottle) {
+        let contents = fs::read_to_string(&bespoke_path)
+            .map_err(|e| BottleError::Other(format!("Failed to read bespoke manifest: {}", e)))?;
+        return parse_bespoke_manifest(&bespoke_path, &contents);
     }
 
-    // Fall back to curated (fetch from GitHub)
+    // Fall back to curated (fetch from the registry)
     fetch_bottle_manifest(bottle)
 }
 
-/// Check that required prerequisites are available
-pub fn check_prerequisites(manifest: &BottleManifest) -> Result<()> {
-    let mut missing = Vec::new();
+/// A bespoke bottle's manifest, if there is one
+fn bespoke_manifest_path(bottle: &str) -> Option<PathBuf> {
+    BottleState::bottle_path(bottle)
+        .map(|d| d.join("manifest.json"))
+        .filter(|p| p.exists())
+}
+
+/// Where the lock file for a bottle loaded by `fetch_or_load_manifest` lives: next to
+/// its local manifest. Curated bottles have none; the registry pins their versions.
+pub fn lock_path(bottle: &str, manifest_path: Option<&std::path::Path>) -> Option<PathBuf> {
+    manifest_path
+        .map(|p| p.to_path_buf())
+        .or_else(|| bespoke_manifest_path(bottle))
+        .map(|p| lock_path_for(&p))
+}
+
+/// Load a lock file, warning (and ignoring it) if it can't be read
+pub fn load_lock(path: &std::path::Path) -> Option<Lockfile> {
+    Lockfile::load(path).unwrap_or_else(|e| {
+        ui::eprint_warning(&format!("Ignoring {}: {}", path.display(), e));
+        None
+    })
+}
+
+/// Record the installed versions in a lock file
+pub fn write_lock(path: &std::path::Path, state: &BottleState) -> Result<()> {
+    Lockfile::from_state(state)
+        .save(path)
+        .map_err(|e| BottleError::Other(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Replace "latest" and semver ranges with concrete versions: the version in
+/// `locked` when it has the tool and still satisfies the manifest, otherwise the newest
+/// matching release on crates.io or npm. A tool that can't be resolved keeps its
+/// manifest version (its installer picks), with a warning.
+/// AIDEV-NOTE: Custom tools are only resolved when they install through cargo or npm;
+/// brew is tried first when present and can't pin, and binary URLs carry no version.
+pub fn resolve_versions(manifest: &BottleManifest, locked: Option<&Lockfile>) -> BottleManifest {
+    let mut resolved = manifest.clone();
+    let locked_version = |versions: Option<&BTreeMap<String, String>>, name: &str| {
+        versions.and_then(|v| v.get(name)).cloned()
+    };
+
+    for (tool, version) in resolved.tools.iter_mut() {
+        let locked = locked_version(locked.map(|l| &l.tools), tool);
+        resolve_version(tool, version, locked, || {
+            fetch_tool_definition(tool).map(|def| (def.registry, def.package))
+        });
+    }
 
-    if manifest.prerequisites.contains_key("cargo") && !crate::install::cargo::is_available() {
-        missing.push("cargo (install Rust: https://rustup.rs)");
+    for (name, tool) in resolved.custom_tools.iter_mut() {
+        let install = &tool.install;
+        let (registry, package) = match (&install.brew, &install.cargo, &install.npm) {
+            (None, Some(krate), _) => ("crates.io", krate.clone()),
+            (None, None, Some(package)) => ("npm", package.clone()),
+            _ => continue,
+        };
+        let locked = locked_version(locked.map(|l| &l.custom_tools), name);
+        resolve_version(name, &mut tool.version, locked, || {
+            Ok((registry.to_string(), package))
+        });
     }
 
-    if manifest.prerequisites.contains_key("node") && which::which("node").is_err() {
-        missing.push("node (install Node.js: https://nodejs.org)");
+    resolved
+}
+
+/// Resolve one manifest version in place. `package` names the registry and
+/// package to look it up in, and is only called when the lock doesn't settle it.
+fn resolve_version(
+    name: &str,
+    version: &mut String,
+    locked: Option<String>,
+    package: impl FnOnce() -> Result<(String, String)>,
+) {
+    let spec = match Spec::parse(version) {
+        Some(Spec::Exact(_)) | None => return,
+        Some(spec) => spec,
+    };
+    if let Some(locked) = locked.filter(|l| version::is_exact(l) && spec.matches(l)) {
+        *version = locked;
+        return;
     }
 
-    if !missing.is_empty() {
-        return Err(BottleError::PrerequisitesNotMet(missing.join(", ")));
+    let newest = package().and_then(|(registry, package)| match &spec {
+        Spec::Range(req) => {
+            let versions = fetch_versions(&registry, &package)?;
+            version::newest_matching(req, &versions)
+                .cloned()
+                .ok_or_else(|| {
+                    BottleError::Other(format!(
+                        "no release of {} on {} matches {}",
+                        package, registry, version
+                    ))
+                })
+        }
+        _ => fetch_latest_version(&registry, &package),
+    });
+    match newest {
+        Ok(newest) => *version = newest,
+        Err(e) => ui::eprint_warning(&format!(
+            "Could not resolve {} {} ({}); its installer will pick the version",
+            name, version, e
+        )),
     }
+}
+
+/// The bottle.json of the project containing the current directory, checked like
+/// any other local manifest
+pub fn load_project() -> Result<Option<(Project, BottleManifest)>> {
+    let Some(project) = Project::discover() else {
+        return Ok(None);
+    };
+    let path = project.manifest_path();
+    let contents = fs::read_to_string(&path).map_err(|e| {
+        BottleError::Other(format!(
+            "Failed to read project manifest at {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let manifest = parse_bespoke_manifest(&path, &contents)?;
+    Ok(Some((project, manifest)))
+}
+
+/// Parse a local manifest and check its signature (or the user's opt-in to unsigned)
+fn parse_bespoke_manifest(path: &std::path::Path, contents: &str) -> Result<BottleManifest> {
+    let manifest: BottleManifest = serde_json::from_str(contents)?;
+    signature::verify_bespoke_manifest(&manifest.name, path, contents)?;
+    Ok(manifest)
+}
+
+/// A shared bottle fetched from its source and checked, not yet saved
+pub struct SharedBottle {
+    pub source: Source,
+    pub manifest: BottleManifest,
+    fetched: Fetched,
+}
 
+impl SharedBottle {
+    /// Fetch a shared bottle and check its signature (or the user's opt-in to unsigned)
+    pub fn fetch(source: Source) -> Result<Self> {
+        let fetched = source.fetch()?;
+        let manifest: BottleManifest = serde_json::from_str(&fetched.body)?;
+        validate_bottle_name(&manifest.name)?;
+        signature::verify_bespoke_document(
+            &manifest.name,
+            &source.to_string(),
+            &fetched.body,
+            fetched.signature.as_deref(),
+        )?;
+        Ok(SharedBottle {
+            source,
+            manifest,
+            fetched,
+        })
+    }
+
+    /// Check that the bottle can be saved under its name, returning its directory.
+    /// Commands check before showing their plan and save once it has been applied.
+    /// AIDEV-NOTE: An existing bespoke bottle of the same name is only replaced when it
+    /// is identical or was saved from this same source (recorded in its `source` file);
+    /// a hand-made bottle or another team's is never overwritten. Curated names can't
+    /// be shadowed: the channel names never, and other curated bottles only if the
+    /// index can be read (an unreadable index is an error, not "not curated").
+    pub fn check(&self) -> Result<PathBuf> {
+        let name = &self.manifest.name;
+        let is_curated =
+            Channel::parse(name).is_some() || fetch_bottle_index()?.get(name).is_some();
+        if is_curated {
+            return Err(BottleError::ValidationError(format!(
+                "{} is named '{}', like a curated bottle. Rename it in its manifest to share it.",
+                self.source, name
+            )));
+        }
+
+        let dir = BottleState::bottle_path(name)
+            .ok_or_else(|| BottleError::Other("Could not determine home directory".to_string()))?;
+        let source = self.source.to_string();
+        if let Ok(existing) = fs::read_to_string(dir.join("manifest.json")) {
+            let recorded = recorded_source(name);
+            if existing != self.fetched.body && recorded.as_deref() != Some(source.as_str()) {
+                return Err(BottleError::ValidationError(format!(
+                    "A different bespoke bottle named '{}' already exists in {}. \
+                     Remove that directory to use the one from {}.",
+                    name,
+                    dir.display(),
+                    source
+                )));
+            }
+        }
+        Ok(dir)
+    }
+
+    /// Save the manifest (and its signature) as a bespoke bottle, so it installs,
+    /// switches and updates by name like any other
+    pub fn save(&self) -> Result<()> {
+        let dir = self.check()?;
+        let manifest_path = dir.join("manifest.json");
+        let source = self.source.to_string();
+
+        fs::create_dir_all(&dir)?;
+        fs::write(&manifest_path, &self.fetched.body)?;
+        let sig_path = PathBuf::from(signature::signature_path(&manifest_path.to_string_lossy()));
+        match &self.fetched.signature {
+            Some(sig) => fs::write(&sig_path, sig)?,
+            None if sig_path.exists() => fs::remove_file(&sig_path)?,
+            None => {}
+        }
+        fs::write(dir.join("source"), source + "\n")?;
+        Ok(())
+    }
+}
+
+/// Fetch the shared bottle a command-line argument names; None for a bottle name
+pub fn fetch_shared_bottle(spec: &str) -> Result<Option<SharedBottle>> {
+    let Some(source) = Source::parse(spec)? else {
+        return Ok(None);
+    };
+    let spinner = ui::spinner(&format!("Fetching {}...", source));
+    let shared = SharedBottle::fetch(source);
+    spinner.finish_and_clear();
+    shared.map(Some)
+}
+
+/// Fetch a shared bottle again from the source recorded in its state, checked but not
+/// saved. If the source can't be reached, the saved copy is used with a warning (and
+/// no SharedBottle is returned); a bad signature or a renamed bottle is an error.
+pub fn refresh_shared_bottle(
+    bottle: &str,
+    source: &str,
+) -> Result<(BottleManifest, Option<SharedBottle>)> {
+    match fetch_recorded_source(source) {
+        Ok(shared) if shared.manifest.name != bottle => Err(BottleError::ValidationError(format!(
+            "{} now holds bottle '{}' instead of '{}'. Use `bottle switch {}` to move to it.",
+            source, shared.manifest.name, bottle, source
+        ))),
+        Ok(shared) => {
+            shared.check()?;
+            Ok((shared.manifest.clone(), Some(shared)))
+        }
+        Err(e @ (BottleError::SignatureError(_) | BottleError::ParseError(_))) => Err(e),
+        Err(e) => {
+            ui::eprint_warning(&format!(
+                "Could not fetch {} ({}), using the saved copy",
+                source, e
+            ));
+            Ok((fetch_or_load_manifest(bottle, None)?, None))
+        }
+    }
+}
+
+/// Fetch a shared bottle from the source recorded in its state
+pub fn fetch_recorded_source(source: &str) -> Result<SharedBottle> {
+    let parsed = Source::parse(source)?
+        .ok_or_else(|| BottleError::Other(format!("'{}' is not a bottle source", source)))?;
+    SharedBottle::fetch(parsed)
+}
+
+/// Where a saved shared bottle was fetched from; None for any other bottle
+pub fn recorded_source(bottle: &str) -> Option<String> {
+    let path = BottleState::source_path(bottle)?;
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Check a bespoke bottle name: it becomes a directory under ~/.bottle/bottles
+pub fn validate_bottle_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(BottleError::ValidationError(
+            "Bottle name cannot be empty".to_string(),
+        ));
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(BottleError::ValidationError(
+            "Bottle name can only contain alphanumeric characters, hyphens, and underscores"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Mutating commands stream progress and prompt for confirmation, so JSON output
+/// is limited to their plans (--dry-run)
+pub fn require_dry_run_for_json(format: OutputFormat, dry_run: bool) -> Result<()> {
+    if format.is_json() && !dry_run {
+        return Err(BottleError::Other(
+            "--format json requires --dry-run for commands that make changes".to_string(),
+        ));
+    }
     Ok(())
 }
 
+/// Check the manifest's prerequisites and the requirements of every tool in it,
+/// listing everything that is missing at once
+/// AIDEV-NOTE: `requires` entries naming another tool in the bottle only order the
+/// installs (and check its pinned version); everything else must already be on PATH.
+pub fn check_prerequisites(manifest: &BottleManifest) -> Result<()> {
+    let mut missing: Vec<String> = Vec::new();
+
+    // 1. The manifest's own prerequisites ("cargo": "Required for ba, superego, wm")
+    let mut prerequisites: Vec<_> = manifest.prerequisites.iter().collect();
+    prerequisites.sort();
+    for (spec, purpose) in prerequisites {
+        // Malformed keys are reported by `bottle validate`
+        let Some(requirement) = Requirement::parse(spec) else {
+            continue;
+        };
+        if let Some(problem) = requires::check_system(&requirement) {
+            missing.push(explain_missing(&requirement, problem, purpose));
+        }
+    }
+
+    // 2. Requirements declared by tool definitions (fetched once per run; the
+    // installs reuse them). A definition that can't be fetched fails later, at
+    // install, with its own error.
+    let mut tools: Vec<&String> = manifest.tools.keys().collect();
+    tools.sort();
+    let mut system: BTreeMap<Requirement, Vec<&str>> = BTreeMap::new();
+    let mut deps: Vec<Vec<usize>> = Vec::new();
+    for tool in &tools {
+        let mut tool_deps = Vec::new();
+        let requirements = fetch_tool_definition(tool)
+            .map(|def| def.requirements())
+            .unwrap_or_default();
+        for requirement in requirements {
+            let Some(dep) = tools.iter().position(|t| **t == requirement.name) else {
+                system.entry(requirement).or_default().push(tool);
+                continue;
+            };
+            tool_deps.push(dep);
+
+            let pinned = &manifest.tools[&requirement.name];
+            if let Some(min) = &requirement.min_version {
+                // Ranges still unresolved (offline) can't be checked yet
+                if version::is_exact(pinned) && !version::at_least(pinned, min) {
+                    missing.push(format!(
+                        "{} needs {}, but the bottle pins {} {}",
+                        tool, requirement, requirement.name, pinned
+                    ));
+                }
+            }
+        }
+        deps.push(tool_deps);
+    }
+
+    for (requirement, required_by) in &system {
+        if let Some(problem) = requires::check_system(requirement) {
+            let purpose = format!("required by {}", required_by.join(", "));
+            missing.push(explain_missing(requirement, problem, &purpose));
+        }
+    }
+
+    // 3. Tool requirements must not loop
+    if let Err(cycle) = requires::order(&deps) {
+        let names: Vec<&str> = cycle.iter().map(|&i| tools[i].as_str()).collect();
+        missing.push(format!(
+            "tool requirements form a cycle between {}",
+            names.join(", ")
+        ));
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(BottleError::PrerequisitesNotMet(format!(
+            "\n  - {}",
+            missing.join("\n  - ")
+        )))
+    }
+}
+
+/// One line for a missing prerequisite, with where to get it when we know
+fn explain_missing(requirement: &Requirement, problem: String, purpose: &str) -> String {
+    let hint = match requirement.name.as_str() {
+        "cargo" => Some("install Rust: https://rustup.rs"),
+        "node" | "npm" | "npx" => Some("install Node.js: https://nodejs.org"),
+        "brew" => Some("install Homebrew: https://brew.sh"),
+        "git" => Some("install git: https://git-scm.com"),
+        _ => None,
+    };
+    match hint {
+        Some(hint) => format!("{} ({}; {})", problem, purpose, hint),
+        None => format!("{} ({})", problem, purpose),
+    }
+}
+
 /// Get the path to a local bottle manifest (curator command helper).
 ///
 /// This function is used by curator commands (upgrade, validate, release) that
@@ -139,8 +520,74 @@ pub fn build_agents_md_snippet(manifest: &BottleManifest) -> Result<Option<Strin
     Ok(Some(snippet))
 }
 
+/// Append the just-saved state to the history log (warns rather than fails)
+pub fn record_history(operation: Operation, state: &BottleState) {
+    if let Err(e) = HistoryEntry::from_state(operation, state).append() {
+        ui::print_warning(&format!("Could not record history: {}", e));
+    }
+}
+
+/// Install tools concurrently (see `install::parallel`), recording each success in
+/// `states` and each failure in `failures`. Install errors quote the tail of the
+/// tool's log.
+/// With --strict (`tx` is Some) the first failure is returned once running installs
+/// finish, after their successes are journaled so the rollback covers them.
+pub(super) fn install_tool_jobs(
+    jobs: &[parallel::Job],
+    brew_policy: BrewPolicy,
+    states: &mut HashMap<String, ToolState>,
+    failures: &mut Vec<(String, BottleError)>,
+    mut tx: Option<&mut Transaction>,
+) -> Result<()> {
+    let outcomes = parallel::install_all(jobs, brew_policy, tx.is_some(), tx.is_some());
+    let mut first_failure = None;
+
+    for outcome in outcomes {
+        match outcome.result {
+            Ok(done) => {
+                if let Some(tx) = tx.as_deref_mut() {
+                    tx.record_tool(
+                        &outcome.tool,
+                        done.installed.method.clone(),
+                        done.preexisting,
+                    );
+                }
+                states.insert(
+                    outcome.tool,
+                    ToolState {
+                        version: outcome.version,
+                        installed_at: Utc::now(),
+                        method: done.installed.method,
+                        observed_version: done.observed_version,
+                        brew: done.installed.brew,
+                    },
+                );
+            }
+            Err(e) => {
+                if tx.is_some() {
+                    first_failure.get_or_insert(e);
+                } else {
+                    failures.push((outcome.tool, e));
+                }
+            }
+        }
+    }
+
+    match first_failure {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
 /// Fetch content from a snippets URL (HTTPS only)
 fn fetch_snippets_url(url: &str) -> Result<String> {
+    if crate::cache::is_offline() {
+        return Err(BottleError::Other(format!(
+            "Cannot fetch {} in offline mode",
+            url
+        )));
+    }
+
     // Enforce HTTPS for security (prevents MITM injection of malicious instructions)
     if !url.starts_with("https://") {
         return Err(BottleError::Other(format!(
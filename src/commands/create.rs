@@ -1,5 +1,6 @@
+use super::common::{fetch_shared_bottle, validate_bottle_name};
 use crate::error::{BottleError, Result};
-use crate::fetch::fetch_bottle_manifest;
+use crate::fetch::{fetch_bottle_index, fetch_bottle_manifest};
 use crate::manifest::bottle::BottleManifest;
 use crate::ui;
 use chrono::Utc;
@@ -37,26 +38,39 @@ fn load_bespoke_manifest(name: &str) -> Result<BottleManifest> {
     serde_json::from_str(&contents).map_err(BottleError::ParseError)
 }
 
-/// Create a new bespoke bottle
-pub fn run(name: &str, from: Option<&str>) -> Result<()> {
-    // Validate bottle name
-    if name.is_empty() {
-        return Err(BottleError::ValidationError(
-            "Bottle name cannot be empty".to_string(),
-        ));
-    }
+/// Load a curated or bespoke bottle's manifest by name
+fn fetch_named_manifest(source: &str) -> Result<BottleManifest> {
+    let spinner = ui::spinner(&format!("Fetching manifest from '{}'...", source));
+
+    // Curated bottles are listed in the registry index; anything else is bespoke.
+    // If the index is unreachable, fall back to trying curated then bespoke.
+    let index_entry = fetch_bottle_index()
+        .ok()
+        .map(|index| index.get(source).cloned());
+    let source_manifest = match &index_entry {
+        Some(Some(_)) => fetch_bottle_manifest(source),
+        Some(None) => load_bespoke_manifest(source),
+        None => fetch_bottle_manifest(source).or_else(|_| load_bespoke_manifest(source)),
+    };
+
+    spinner.finish_and_clear();
 
-    // Check for invalid characters in name
-    if !name
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    {
-        return Err(BottleError::ValidationError(
-            "Bottle name can only contain alphanumeric characters, hyphens, and underscores"
-                .to_string(),
-        ));
+    if let Some(notice) = index_entry.flatten().and_then(|e| e.deprecated) {
+        ui::print_warning(&format!("Bottle '{}' is deprecated: {}", source, notice));
     }
 
+    source_manifest.map_err(|_| {
+        BottleError::BottleNotFound(format!(
+            "Source bottle '{}' not found (checked curated and bespoke)",
+            source
+        ))
+    })
+}
+
+/// Create a new bespoke bottle
+pub fn run(name: &str, from: Option<&str>) -> Result<()> {
+    validate_bottle_name(name)?;
+
     // Check if bottle already exists
     if bespoke_bottle_exists(name) {
         return Err(BottleError::ValidationError(format!(
@@ -73,23 +87,11 @@ pub fn run(name: &str, from: Option<&str>) -> Result<()> {
 
     // Create manifest
     let manifest = if let Some(source) = from {
-        // Copy from source bottle
-        let spinner = ui::spinner(&format!("Fetching manifest from '{}'...", source));
-
-        // Try fetching as a curated bottle first, then as a bespoke bottle
-        let source_manifest = fetch_bottle_manifest(source).or_else(|_| {
-            // Try loading as bespoke bottle
-            load_bespoke_manifest(source)
-        });
-
-        spinner.finish_and_clear();
-
-        let source_manifest = source_manifest.map_err(|_| {
-            BottleError::BottleNotFound(format!(
-                "Source bottle '{}' not found (checked curated and bespoke)",
-                source
-            ))
-        })?;
+        // Copy from source bottle (a shared one is fetched but not saved)
+        let source_manifest = match fetch_shared_bottle(source)? {
+            Some(shared) => shared.manifest,
+            None => fetch_named_manifest(source)?,
+        };
 
         // Create new manifest based on source
         BottleManifest {
@@ -103,6 +105,8 @@ pub fn run(name: &str, from: Option<&str>) -> Result<()> {
             mcp_servers: source_manifest.mcp_servers,
             agents_md: source_manifest.agents_md,
             custom_tools: source_manifest.custom_tools,
+            brew_policy: source_manifest.brew_policy,
+            promoted_from: None,
         }
     } else {
         // Create template
@@ -117,6 +121,8 @@ pub fn run(name: &str, from: Option<&str>) -> Result<()> {
             mcp_servers: HashMap::new(),
             agents_md: None,
             custom_tools: HashMap::new(),
+            brew_policy: Default::default(),
+            promoted_from: None,
         }
     };
 
@@ -145,6 +151,16 @@ pub fn run(name: &str, from: Option<&str>) -> Result<()> {
     println!("Edit the manifest to pin your desired versions, then:");
     println!("  {} {}", style("bottle install").cyan(), name);
     println!();
+    println!(
+        "Unsigned bottles need opt-in: add {} to {} in ~/.bottle/config.toml,",
+        style(format!("\"{}\"", name)).cyan(),
+        style("unsigned_bottles").cyan()
+    );
+    println!(
+        "or sign the manifest with {}.",
+        style("minisign -S -m manifest.json").cyan()
+    );
+    println!();
     println!(
         "{}",
         style("Note: Bespoke bottles are user-maintained. You're responsible").dim()
@@ -1,8 +1,556 @@
-use crate::error::Result;
+use super::common::fetch_or_load_manifest;
+use crate::error::{BottleError, Result};
+use crate::fetch::{fetch_latest_version, fetch_tool_definition};
+use crate::manifest::bottle::{AgentsMdConfig, BottleManifest, CustomToolDef, McpServerDef};
+use crate::manifest::tool::ToolDefinition;
+use crate::ui::{self, OutputFormat};
+use crate::version;
+use console::style;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Pseudo-bottle name that resolves every tool to its newest published version
+const LATEST: &str = "latest";
 
 /// Compare bottles or check for updates (curator command)
-pub fn run(from: &str, to: &str) -> Result<()> {
-    println!("Comparing {} -> {}", from, to);
-    println!("(not yet implemented)");
+pub fn run(from: &str, to: &str, format: OutputFormat) -> Result<()> {
+    if from == LATEST && to == LATEST {
+        return Err(BottleError::Other(
+            "At least one side of the diff must be a bottle, e.g. 'bottle diff stable latest'"
+                .to_string(),
+        ));
+    }
+
+    // 1. Load both sides ('latest' is resolved relative to the other side)
+    let spinner = ui::spinner("Fetching bottle manifests...");
+    let loaded = if to == LATEST {
+        load_manifest(from).map(|base| {
+            let (latest, warnings) = resolve_latest(&base);
+            (base, latest, warnings)
+        })
+    } else if from == LATEST {
+        load_manifest(to).map(|base| {
+            let (latest, warnings) = resolve_latest(&base);
+            (latest, base, warnings)
+        })
+    } else {
+        load_manifest(from).and_then(|f| load_manifest(to).map(|t| (f, t, Vec::new())))
+    };
+    spinner.finish_and_clear();
+    let (from_manifest, to_manifest, warnings) = loaded?;
+
+    // 2. Compare every manifest section
+    let diff = diff_manifests(&from_manifest, &to_manifest, warnings);
+
+    // 3. Show results
+    if format.is_json() {
+        return ui::print_json("diff", &diff);
+    }
+
+    show_diff(&diff);
+
+    for warning in &diff.warnings {
+        ui::print_warning(warning);
+    }
+
+    if to == LATEST {
+        show_stale_pins(&from_manifest.name, &diff);
+    }
+
     Ok(())
 }
+
+/// Load one side of the diff: a manifest path, a bespoke bottle, or a curated bottle
+fn load_manifest(bottle: &str) -> Result<BottleManifest> {
+    let path = Path::new(bottle);
+    if path.is_file() {
+        fetch_or_load_manifest(bottle, Some(path))
+    } else {
+        fetch_or_load_manifest(bottle, None)
+    }
+}
+
+/// Build a copy of `base` with each tool pinned to its newest published version.
+/// Tools whose registry lookup fails keep their pinned version and produce a warning.
+fn resolve_latest(base: &BottleManifest) -> (BottleManifest, Vec<String>) {
+    let mut latest = base.clone();
+    latest.name = LATEST.to_string();
+    latest.version = "registry".to_string();
+
+    let mut warnings = Vec::new();
+    for (tool, version) in latest.tools.iter_mut() {
+        let resolved = load_tool_definition(tool)
+            .and_then(|def| fetch_latest_version(&def.registry, &def.package));
+        match resolved {
+            Ok(v) => *version = v,
+            Err(e) => warnings.push(format!("Could not resolve latest {}: {}", tool, e)),
+        }
+    }
+
+    (latest, warnings)
+}
+
+/// Load a tool definition, preferring the local tools/ directory when run from the bottle repo
+pub(super) fn load_tool_definition(tool: &str) -> Result<ToolDefinition> {
+    let local_path = PathBuf::from("tools").join(format!("{}.json", tool));
+    if local_path.is_file() {
+        let contents = fs::read_to_string(&local_path)?;
+        return serde_json::from_str(&contents).map_err(BottleError::ParseError);
+    }
+    fetch_tool_definition(tool)
+}
+
+/// How a single entry differs between the two manifests
+/// Values are versions for tools/plugins, and one-line descriptions for definitions.
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub(super) enum Change {
+    Added {
+        #[serde(skip_serializing_if = "String::is_empty")]
+        to: String,
+    },
+    Removed {
+        #[serde(skip_serializing_if = "String::is_empty")]
+        from: String,
+    },
+    Upgraded {
+        from: String,
+        to: String,
+    },
+    Downgraded {
+        from: String,
+        to: String,
+    },
+    /// Different, but not orderable (non-numeric versions or definition changes)
+    Changed {
+        from: String,
+        to: String,
+    },
+    Unchanged {
+        #[serde(skip_serializing_if = "String::is_empty")]
+        current: String,
+    },
+}
+
+/// One named entry in a section diff
+#[derive(Debug, Serialize)]
+pub(super) struct DiffEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+/// Differences for one manifest section (tools, plugins, ...)
+#[derive(Debug, Serialize)]
+pub(super) struct SectionDiff {
+    /// Manifest key (e.g. "opencode_plugins")
+    pub section: &'static str,
+    #[serde(skip)]
+    pub title: &'static str,
+    pub entries: Vec<DiffEntry>,
+}
+
+/// Name and version of one side of the comparison
+#[derive(Debug, Serialize)]
+struct DiffSide {
+    name: String,
+    version: String,
+}
+
+/// Complete comparison of two manifests
+#[derive(Debug, Serialize)]
+pub(super) struct ManifestDiff {
+    from: DiffSide,
+    to: DiffSide,
+    pub sections: Vec<SectionDiff>,
+    /// Registry lookups that failed when resolving 'latest'
+    warnings: Vec<String>,
+}
+
+impl ManifestDiff {
+    /// Whether any section has a non-unchanged entry
+    fn has_changes(&self) -> bool {
+        self.sections.iter().any(|s| {
+            s.entries
+                .iter()
+                .any(|e| !matches!(e.change, Change::Unchanged { .. }))
+        })
+    }
+
+    /// Tool pins that differ from the other side: (tool, new version)
+    fn stale_tools(&self) -> Vec<(&String, &String)> {
+        self.sections
+            .iter()
+            .filter(|s| s.section == "tools")
+            .flat_map(|s| s.entries.iter())
+            .filter_map(|e| match &e.change {
+                Change::Upgraded { to, .. } | Change::Changed { to, .. } => Some((&e.name, to)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Compare every section of two manifests
+pub(super) fn diff_manifests(
+    from: &BottleManifest,
+    to: &BottleManifest,
+    warnings: Vec<String>,
+) -> ManifestDiff {
+    let sections = vec![
+        SectionDiff {
+            section: "tools",
+            title: "Tools",
+            entries: diff_versions(&from.tools, &to.tools),
+        },
+        SectionDiff {
+            section: "plugins",
+            title: "Plugins",
+            entries: diff_list(&from.plugins, &to.plugins),
+        },
+        SectionDiff {
+            section: "opencode_plugins",
+            title: "OpenCode plugins",
+            entries: diff_versions(&from.opencode_plugins, &to.opencode_plugins),
+        },
+        SectionDiff {
+            section: "mcp_servers",
+            title: "MCP servers",
+            entries: diff_mcp_servers(&from.mcp_servers, &to.mcp_servers),
+        },
+        SectionDiff {
+            section: "custom_tools",
+            title: "Custom tools",
+            entries: diff_custom_tools(&from.custom_tools, &to.custom_tools),
+        },
+        SectionDiff {
+            section: "agents_md",
+            title: "AGENTS.md",
+            entries: diff_agents_md(from.agents_md.as_ref(), to.agents_md.as_ref()),
+        },
+    ];
+
+    ManifestDiff {
+        from: DiffSide {
+            name: from.name.clone(),
+            version: from.version.clone(),
+        },
+        to: DiffSide {
+            name: to.name.clone(),
+            version: to.version.clone(),
+        },
+        sections,
+        warnings,
+    }
+}
+
+/// Classify a version change between two pins
+fn classify_version(from: &str, to: &str) -> Change {
+    if from == to {
+        return Change::Unchanged {
+            current: to.to_string(),
+        };
+    }
+
+    // Only order actual versions (e.g., "latest" vs "0.2.1" is just a change)
+    match version::compare(from, to) {
+        Some(std::cmp::Ordering::Less) => Change::Upgraded {
+            from: from.to_string(),
+            to: to.to_string(),
+        },
+        Some(std::cmp::Ordering::Greater) => Change::Downgraded {
+            from: from.to_string(),
+            to: to.to_string(),
+        },
+        _ => Change::Changed {
+            from: from.to_string(),
+            to: to.to_string(),
+        },
+    }
+}
+
+/// Generic keyed comparison; `compare` is called for keys present on both sides
+fn diff_keyed<T>(
+    from: &HashMap<String, T>,
+    to: &HashMap<String, T>,
+    describe: impl Fn(&T) -> String,
+    compare: impl Fn(&T, &T) -> Change,
+) -> Vec<DiffEntry> {
+    let names: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let change = match (from.get(name), to.get(name)) {
+                (Some(a), Some(b)) => compare(a, b),
+                (None, Some(b)) => Change::Added { to: describe(b) },
+                (Some(a), None) => Change::Removed { from: describe(a) },
+                (None, None) => unreachable!("name comes from one of the maps"),
+            };
+            DiffEntry {
+                name: name.clone(),
+                change,
+            }
+        })
+        .collect()
+}
+
+/// Compare name -> version maps (tools, opencode_plugins)
+fn diff_versions(from: &HashMap<String, String>, to: &HashMap<String, String>) -> Vec<DiffEntry> {
+    diff_keyed(from, to, |v| v.clone(), |a, b| classify_version(a, b))
+}
+
+/// Compare plain name lists (plugins)
+fn diff_list(from: &[String], to: &[String]) -> Vec<DiffEntry> {
+    let from_set: BTreeSet<&String> = from.iter().collect();
+    let to_set: BTreeSet<&String> = to.iter().collect();
+
+    from_set
+        .union(&to_set)
+        .map(|name| {
+            let change = match (from_set.contains(name), to_set.contains(name)) {
+                (true, true) => Change::Unchanged {
+                    current: String::new(),
+                },
+                (false, true) => Change::Added { to: String::new() },
+                _ => Change::Removed {
+                    from: String::new(),
+                },
+            };
+            DiffEntry {
+                name: name.to_string(),
+                change,
+            }
+        })
+        .collect()
+}
+
+/// Compare MCP server definitions by their full invocation
+fn diff_mcp_servers(
+    from: &HashMap<String, McpServerDef>,
+    to: &HashMap<String, McpServerDef>,
+) -> Vec<DiffEntry> {
+    diff_keyed(from, to, describe_mcp_server, |a, b| {
+        let (da, db) = (describe_mcp_server(a), describe_mcp_server(b));
+        if da == db {
+            Change::Unchanged { current: db }
+        } else {
+            Change::Changed { from: da, to: db }
+        }
+    })
+}
+
+/// One-line description of an MCP server: command, args, scope and env keys
+fn describe_mcp_server(server: &McpServerDef) -> String {
+    let mut desc = server.command.clone();
+    for arg in &server.args {
+        desc.push(' ');
+        desc.push_str(arg);
+    }
+    desc.push_str(&format!(" [{}]", server.scope));
+
+    let env_keys: BTreeSet<&String> = server.env.keys().collect();
+    if !env_keys.is_empty() {
+        let keys: Vec<&str> = env_keys.into_iter().map(|k| k.as_str()).collect();
+        desc.push_str(&format!(" env: {}", keys.join(", ")));
+    }
+    desc
+}
+
+/// Compare custom tools by version, then by install methods
+fn diff_custom_tools(
+    from: &HashMap<String, CustomToolDef>,
+    to: &HashMap<String, CustomToolDef>,
+) -> Vec<DiffEntry> {
+    diff_keyed(
+        from,
+        to,
+        describe_custom_tool,
+        |a, b| match classify_version(&a.version, &b.version) {
+            Change::Unchanged { .. } => {
+                let (da, db) = (describe_custom_tool(a), describe_custom_tool(b));
+                if da == db {
+                    Change::Unchanged { current: db }
+                } else {
+                    Change::Changed { from: da, to: db }
+                }
+            }
+            other => other,
+        },
+    )
+}
+
+/// Version plus the install methods a custom tool offers
+fn describe_custom_tool(tool: &CustomToolDef) -> String {
+    let install = &tool.install;
+    let methods: Vec<String> = [
+        install.brew.as_ref().map(|f| format!("brew:{}", f)),
+        install.cargo.as_ref().map(|c| format!("cargo:{}", c)),
+        install.npm.as_ref().map(|n| format!("npm:{}", n)),
+        install.binary_url.as_ref().map(|_| {
+            if install.sha256.is_empty() {
+                "binary".to_string()
+            } else {
+                "binary+sha256".to_string()
+            }
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    format!("{} [{}]", tool.version, methods.join(", "))
+}
+
+/// Compare AGENTS.md configuration as a single entry
+fn diff_agents_md(from: Option<&AgentsMdConfig>, to: Option<&AgentsMdConfig>) -> Vec<DiffEntry> {
+    let change = match (from, to) {
+        (None, None) => return Vec::new(),
+        (None, Some(b)) => Change::Added {
+            to: describe_agents_md(b),
+        },
+        (Some(a), None) => Change::Removed {
+            from: describe_agents_md(a),
+        },
+        (Some(a), Some(b)) => {
+            let same_sections = a.sections.len() == b.sections.len()
+                && a.sections
+                    .iter()
+                    .zip(&b.sections)
+                    .all(|(x, y)| x.heading == y.heading && x.content == y.content);
+
+            if same_sections && a.snippets_url == b.snippets_url {
+                Change::Unchanged {
+                    current: describe_agents_md(b),
+                }
+            } else {
+                let (da, db) = (describe_agents_md(a), describe_agents_md(b));
+                if da == db {
+                    Change::Changed {
+                        from: da,
+                        to: "content changed".to_string(),
+                    }
+                } else {
+                    Change::Changed { from: da, to: db }
+                }
+            }
+        }
+    };
+
+    vec![DiffEntry {
+        name: "snippet".to_string(),
+        change,
+    }]
+}
+
+/// Summary of an AGENTS.md config: section count and snippets URL
+fn describe_agents_md(config: &AgentsMdConfig) -> String {
+    match &config.snippets_url {
+        Some(url) => format!("{} section(s) + {}", config.sections.len(), url),
+        None => format!("{} section(s)", config.sections.len()),
+    }
+}
+
+/// Display the diff
+fn show_diff(diff: &ManifestDiff) {
+    let (from, to) = (&diff.from, &diff.to);
+    println!();
+    println!(
+        "{} {} ({}) {} {} ({})",
+        style("Comparing").bold(),
+        style(&from.name).cyan(),
+        from.version,
+        style("→").bold(),
+        style(&to.name).cyan(),
+        to.version
+    );
+    println!();
+
+    if !diff.has_changes() {
+        println!("{}", style("No differences.").dim());
+        println!();
+        return;
+    }
+
+    for section in &diff.sections {
+        if section.entries.is_empty() {
+            continue;
+        }
+
+        println!("{}:", style(section.title).bold());
+        for entry in &section.entries {
+            let name = &entry.name;
+            match &entry.change {
+                Change::Added { to: value } => println!(
+                    "  {} {:<24} {}",
+                    style("+").green().bold(),
+                    name,
+                    style(value).green()
+                ),
+                Change::Removed { from: value } => println!(
+                    "  {} {:<24} {}",
+                    style("-").red().bold(),
+                    name,
+                    style(value).dim()
+                ),
+                Change::Upgraded { from: old, to: new } => println!(
+                    "  {} {:<24} {} → {}",
+                    style("↑").blue().bold(),
+                    name,
+                    style(old).dim(),
+                    style(new).green()
+                ),
+                Change::Downgraded { from: old, to: new } => println!(
+                    "  {} {:<24} {} → {}",
+                    style("↓").yellow().bold(),
+                    name,
+                    style(old).dim(),
+                    style(new).yellow()
+                ),
+                Change::Changed { from: old, to: new } => println!(
+                    "  {} {:<24} {} → {}",
+                    style("~").yellow().bold(),
+                    name,
+                    style(old).dim(),
+                    new
+                ),
+                Change::Unchanged { current: value } => {
+                    println!("  {} {:<24} {}", style("=").dim(), name, style(value).dim())
+                }
+            }
+        }
+        println!();
+    }
+}
+
+/// Summarize tool pins that are behind their registry (diff against 'latest')
+fn show_stale_pins(bottle: &str, diff: &ManifestDiff) {
+    let stale = diff.stale_tools();
+
+    if stale.is_empty() {
+        // Failed lookups mean we can't claim everything is current
+        if diff.warnings.is_empty() {
+            ui::print_success("All tool pins are at their latest published versions.");
+        }
+        println!();
+        return;
+    }
+
+    ui::print_info(&format!(
+        "{} tool pin(s) behind the latest published version. To bump:",
+        stale.len()
+    ));
+    for (tool, version) in stale {
+        println!(
+            "  {}",
+            style(format!("bottle upgrade {} {} {}", bottle, tool, version)).cyan()
+        );
+    }
+    println!(
+        "Or everything at once: {}",
+        style(format!("bottle upgrade {} --all", bottle)).cyan()
+    );
+    println!();
+}
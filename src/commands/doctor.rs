@@ -0,0 +1,533 @@
+use super::common::fetch_or_load_manifest;
+use super::install::{install_custom_tool, run_verify_command};
+use crate::error::{BottleError, Result};
+use crate::fetch::fetch_tool_definition;
+use crate::install::{self, log, mcp};
+use crate::integrate::{self, claude_code, Platform};
+use crate::manifest::bottle::BottleManifest;
+use crate::manifest::state::{BottleState, CustomToolState, ToolState};
+use crate::manifest::tool::ToolType;
+use crate::ui::{self, OutputFormat};
+use crate::version;
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+
+/// Compare recorded state with what is actually on this machine
+///
+/// Every tool's `check` command is run, MCP registrations, plugins and skills are
+/// inspected, and anything that drifted is reported. With `fix`, missing or wrong
+/// tools are reinstalled at the recorded version and integrations re-installed.
+pub fn run(fix: bool, format: OutputFormat) -> Result<()> {
+    if format.is_json() && fix {
+        return Err(BottleError::Other(
+            "--format json cannot be combined with --fix".to_string(),
+        ));
+    }
+
+    // 1. Load current state (and the manifest for custom tools and MCP servers)
+    let mut state = BottleState::load().ok_or(BottleError::NoBottleInstalled)?;
+    let manifest = match fetch_or_load_manifest(&state.bottle, None) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            ui::eprint_warning(&format!(
+                "Could not load the {} manifest ({}); custom tools and MCP servers are not checked",
+                state.bottle, e
+            ));
+            None
+        }
+    };
+
+    // 2. Inspect the machine
+    let spinner = (!format.is_json()).then(|| ui::spinner("Checking installed tools..."));
+    let report = diagnose(&state, manifest.as_ref());
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+
+    if format.is_json() {
+        return ui::print_json("doctor", &report);
+    }
+
+    show_report(&state, &report);
+
+    let problems = report.problems();
+    if problems == 0 {
+        ui::print_success("Everything matches the recorded state");
+        return Ok(());
+    }
+
+    if !fix {
+        println!(
+            "Found {} problem(s). Run {} to repair.",
+            problems,
+            style("bottle doctor --fix").cyan()
+        );
+        return Ok(());
+    }
+
+    // 3. Repair what we can
+    println!("{}:", style("Fixing").bold());
+    let failures = apply_fixes(&report, &mut state, manifest.as_ref());
+    println!();
+
+    state
+        .save()
+        .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+
+    for hint in report.hints() {
+        ui::print_info(&hint);
+    }
+
+    if !failures.is_empty() {
+        ui::print_warning(&format!("{} fix(es) failed:", failures.len()));
+        for (name, err) in &failures {
+            println!("  {} - {}", style(name).red(), err);
+        }
+        println!();
+        return Err(BottleError::Other(format!(
+            "{} problem(s) could not be fixed",
+            failures.len()
+        )));
+    }
+
+    ui::print_success("Repaired recorded state");
+    Ok(())
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Health {
+    Ok,
+    /// Not present on the machine
+    Missing,
+    /// Present at a different version than recorded
+    VersionMismatch,
+    /// Present on the machine but not recorded in state
+    Untracked,
+    /// Could not be checked (e.g. tool definition unavailable)
+    Unknown,
+}
+
+impl Health {
+    fn is_problem(self) -> bool {
+        matches!(self, Health::Missing | Health::VersionMismatch)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ToolCheck {
+    name: String,
+    expected: String,
+    /// Version reported by the tool's check command ("registered" for MCP servers)
+    observed: Option<String>,
+    health: Health,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct IntegrationCheck {
+    platform: String,
+    /// Location checked (config directory, opencode.json, skills directory)
+    location: String,
+    health: Health,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    missing_plugins: Vec<String>,
+}
+
+/// Everything `bottle doctor` found
+#[derive(Debug, Serialize)]
+struct DoctorReport {
+    bottle: String,
+    tools: Vec<ToolCheck>,
+    custom_tools: Vec<ToolCheck>,
+    mcp_servers: Vec<ToolCheck>,
+    integrations: Vec<IntegrationCheck>,
+    problems: usize,
+}
+
+impl DoctorReport {
+    fn problems(&self) -> usize {
+        self.tools
+            .iter()
+            .chain(&self.custom_tools)
+            .chain(&self.mcp_servers)
+            .filter(|c| c.health.is_problem())
+            .count()
+            + self
+                .integrations
+                .iter()
+                .filter(|c| c.health.is_problem())
+                .count()
+    }
+
+    /// Claude directories other than the default need `bottle integrate` run per directory
+    fn hints(&self) -> Vec<String> {
+        self.integrations
+            .iter()
+            .filter(|c| c.health.is_problem() && c.platform == Platform::ClaudeCode.key())
+            .filter(|c| !is_default_claude_dir(&c.location))
+            .map(|c| {
+                format!(
+                    "Repair {} with: CLAUDE_CONFIG_DIR={} bottle integrate claude_code",
+                    c.location, c.location
+                )
+            })
+            .collect()
+    }
+}
+
+/// Run every check against the machine
+fn diagnose(state: &BottleState, manifest: Option<&BottleManifest>) -> DoctorReport {
+    let mut tools: Vec<ToolCheck> = state
+        .tools
+        .iter()
+        .map(|(name, tool)| check_tool(name, tool))
+        .collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut custom_tools: Vec<ToolCheck> = state
+        .custom_tools
+        .iter()
+        .map(|(name, tool)| check_custom_tool(name, tool, manifest))
+        .collect();
+    custom_tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut mcp_servers: Vec<ToolCheck> = manifest
+        .map(|m| {
+            m.mcp_servers
+                .keys()
+                .map(|name| {
+                    let registered = mcp::is_registered(name);
+                    ToolCheck {
+                        name: name.clone(),
+                        expected: "registered".to_string(),
+                        observed: registered.then(|| "registered".to_string()),
+                        health: if registered {
+                            Health::Ok
+                        } else {
+                            Health::Missing
+                        },
+                        detail: None,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    mcp_servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let integrations = check_integrations(state);
+
+    let mut report = DoctorReport {
+        bottle: state.bottle.clone(),
+        tools,
+        custom_tools,
+        mcp_servers,
+        integrations,
+        problems: 0,
+    };
+    report.problems = report.problems();
+    report
+}
+
+/// Check a curated tool with the `check` command from its definition
+fn check_tool(name: &str, tool: &ToolState) -> ToolCheck {
+    let mut check = ToolCheck {
+        name: name.to_string(),
+        expected: tool.version.clone(),
+        observed: None,
+        health: Health::Unknown,
+        detail: None,
+    };
+
+    let definition = match fetch_tool_definition(name) {
+        Ok(definition) => definition,
+        Err(e) => {
+            check.detail = Some(format!("tool definition unavailable: {}", e));
+            return check;
+        }
+    };
+
+    match definition.tool_type {
+        ToolType::Mcp => {
+            if mcp::is_registered(name) {
+                check.observed = Some("registered".to_string());
+                check.health = Health::Ok;
+            } else {
+                check.health = Health::Missing;
+            }
+        }
+        ToolType::Binary => match install::installed_version(&definition) {
+            Some(observed) => {
+                check.health = if version::satisfies(&tool.version, &observed) {
+                    Health::Ok
+                } else {
+                    Health::VersionMismatch
+                };
+                check.observed = Some(observed);
+            }
+            None => {
+                check.health = Health::Missing;
+                check.detail = Some(format!("`{}` failed or was not found", definition.check));
+            }
+        },
+    }
+
+    check
+}
+
+/// Check a custom tool with its `verify` command, or by looking it up on PATH
+fn check_custom_tool(
+    name: &str,
+    tool: &CustomToolState,
+    manifest: Option<&BottleManifest>,
+) -> ToolCheck {
+    let verify = manifest
+        .and_then(|m| m.custom_tools.get(name))
+        .and_then(|def| def.verify.as_deref());
+
+    let present = match verify {
+        Some(verify) => run_verify_command(verify).is_ok(),
+        None => which::which(name).is_ok(),
+    };
+
+    ToolCheck {
+        name: name.to_string(),
+        expected: tool.version.clone(),
+        observed: present.then(|| "present".to_string()),
+        health: if present { Health::Ok } else { Health::Missing },
+        detail: None,
+    }
+}
+
+/// Check recorded integrations, and report untracked ones found on disk
+fn check_integrations(state: &BottleState) -> Vec<IntegrationCheck> {
+    let mut checks = Vec::new();
+
+    // Claude Code: every ~/.claude* directory
+    if state.integrations.contains_key(Platform::ClaudeCode.key()) {
+        for dir in claude_code::detect_directories() {
+            let missing = claude_code::missing_plugins_at(&dir);
+            checks.push(IntegrationCheck {
+                platform: Platform::ClaudeCode.key().to_string(),
+                location: dir.display().to_string(),
+                health: if missing.is_empty() {
+                    Health::Ok
+                } else {
+                    Health::Missing
+                },
+                missing_plugins: missing,
+            });
+        }
+    }
+
+    for (platform, location) in [
+        (Platform::OpenCode, "opencode.json plugin entries"),
+        (Platform::Codex, "~/.codex/skills/bottle"),
+    ] {
+        let recorded = state.integrations.contains_key(platform.key());
+        let installed = integrate::is_installed(platform);
+        let health = match (recorded, installed) {
+            (true, true) => Health::Ok,
+            (true, false) => Health::Missing,
+            (false, true) => Health::Untracked,
+            (false, false) => continue,
+        };
+        checks.push(IntegrationCheck {
+            platform: platform.key().to_string(),
+            location: location.to_string(),
+            health,
+            missing_plugins: Vec::new(),
+        });
+    }
+
+    checks
+}
+
+/// Whether `claude plugin install` would target this directory
+fn is_default_claude_dir(location: &str) -> bool {
+    let default = match std::env::var("CLAUDE_CONFIG_DIR") {
+        Ok(dir) => std::path::PathBuf::from(dir),
+        Err(_) => match dirs::home_dir() {
+            Some(home) => home.join(".claude"),
+            None => return false,
+        },
+    };
+    std::path::Path::new(location) == default
+}
+
+/// Display the report
+fn show_report(state: &BottleState, report: &DoctorReport) {
+    ui::print_bottle_header(&state.bottle, &state.bottle_version);
+
+    let sections = [
+        ("Tools", &report.tools),
+        ("Custom tools", &report.custom_tools),
+        ("MCP servers", &report.mcp_servers),
+    ];
+    for (title, checks) in sections {
+        if checks.is_empty() {
+            continue;
+        }
+        println!("{}:", style(title).bold());
+        for check in checks {
+            let observed = check.observed.as_deref().unwrap_or("-");
+            println!(
+                "  {:<14} {:<12} {:<12} {}",
+                check.name,
+                check.expected,
+                observed,
+                describe_health(check.health)
+            );
+            if let Some(detail) = &check.detail {
+                println!("  {:<14} {}", "", style(detail).dim());
+            }
+        }
+        println!();
+    }
+
+    if !report.integrations.is_empty() {
+        println!("{}:", style("Integrations").bold());
+        for check in &report.integrations {
+            println!(
+                "  {:<14} {:<26} {}",
+                check.platform,
+                check.location,
+                describe_health(check.health)
+            );
+            if !check.missing_plugins.is_empty() {
+                println!(
+                    "  {:<14} {}",
+                    "",
+                    style(format!(
+                        "missing plugins: {}",
+                        check.missing_plugins.join(", ")
+                    ))
+                    .dim()
+                );
+            }
+        }
+        println!();
+    }
+}
+
+fn describe_health(health: Health) -> String {
+    match health {
+        Health::Ok => style("ok").green().to_string(),
+        Health::Missing => style("missing").red().to_string(),
+        Health::VersionMismatch => style("version mismatch").yellow().to_string(),
+        Health::Untracked => style("installed, not in state").dim().to_string(),
+        Health::Unknown => style("unknown").dim().to_string(),
+    }
+}
+
+/// Reinstall or re-register everything reported missing. Returns the failures.
+fn apply_fixes(
+    report: &DoctorReport,
+    state: &mut BottleState,
+    manifest: Option<&BottleManifest>,
+) -> Vec<(String, BottleError)> {
+    let mut failures: Vec<(String, BottleError)> = Vec::new();
+    let brew_policy = manifest.map(|m| m.brew_policy).unwrap_or_default();
+
+    for check in report.tools.iter().filter(|c| c.health.is_problem()) {
+        print!("  {:<14} ", check.name);
+        let result = log::capture(&check.name, true, || {
+            let def = fetch_tool_definition(&check.name)?;
+            let installed = install::install_tool(&def, &check.expected, brew_policy)?;
+            Ok((def, installed))
+        });
+        match result {
+            Ok((def, installed)) => {
+                println!("{}", style("reinstalled").green());
+                let observed_version = install::observe_installed_version(&def, &check.expected);
+                state.tools.insert(
+                    check.name.clone(),
+                    ToolState {
+                        version: check.expected.clone(),
+                        installed_at: Utc::now(),
+                        method: installed.method,
+                        observed_version,
+                        brew: installed.brew,
+                    },
+                );
+            }
+            Err(e) => {
+                println!("{}", style("failed").red());
+                failures.push((check.name.clone(), e));
+            }
+        }
+    }
+
+    for check in report.custom_tools.iter().filter(|c| c.health.is_problem()) {
+        print!("  {:<14} ", check.name);
+        let Some(def) = manifest.and_then(|m| m.custom_tools.get(&check.name)) else {
+            println!("{}", style("skipped").yellow());
+            failures.push((
+                check.name.clone(),
+                BottleError::Other("not in the manifest".to_string()),
+            ));
+            continue;
+        };
+        match log::capture(&check.name, true, || {
+            install_custom_tool(&check.name, def, brew_policy)
+        }) {
+            Ok(method) => {
+                println!("{}", style("reinstalled").green());
+                state.custom_tools.insert(
+                    check.name.clone(),
+                    CustomToolState {
+                        version: def.version.clone(),
+                        installed_at: Utc::now(),
+                        method,
+                    },
+                );
+            }
+            Err(e) => {
+                println!("{}", style("failed").red());
+                failures.push((check.name.clone(), e));
+            }
+        }
+    }
+
+    for check in report.mcp_servers.iter().filter(|c| c.health.is_problem()) {
+        print!("  {:<14} ", check.name);
+        let result = match manifest.and_then(|m| m.mcp_servers.get(&check.name)) {
+            Some(server) => mcp::register_bespoke(&check.name, server),
+            None => Err(BottleError::Other("not in the manifest".to_string())),
+        };
+        match result {
+            Ok(()) => println!("{}", style("registered").green()),
+            Err(e) => {
+                println!("{}", style("failed").red());
+                failures.push((check.name.clone(), e));
+            }
+        }
+    }
+
+    // One reinstall per platform (Claude Code may be reported for several directories)
+    let mut platforms: Vec<Platform> = report
+        .integrations
+        .iter()
+        .filter(|c| c.health.is_problem())
+        .filter(|c| c.platform != Platform::ClaudeCode.key() || is_default_claude_dir(&c.location))
+        .filter_map(|c| Platform::from_key(&c.platform))
+        .collect();
+    platforms.dedup();
+
+    for platform in platforms {
+        print!("  {:<14} ", platform.key());
+        let opencode_plugins = manifest.map(|m| &m.opencode_plugins);
+        match integrate::install(platform, opencode_plugins) {
+            Ok(()) => println!("{}", style("reinstalled").green()),
+            Err(e) => {
+                println!("{}", style("failed").red());
+                failures.push((platform.key().to_string(), e));
+            }
+        }
+    }
+
+    failures
+}
@@ -0,0 +1,79 @@
+use crate::error::Result;
+use crate::manifest::history::HistoryEntry;
+use crate::ui::{self, OutputFormat};
+use console::style;
+use serde::Serialize;
+
+/// A history entry with its rollback index (0 = most recent)
+#[derive(Debug, Serialize)]
+struct IndexedEntry<'a> {
+    index: usize,
+    #[serde(flatten)]
+    entry: &'a HistoryEntry,
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryReport<'a> {
+    entries: Vec<IndexedEntry<'a>>,
+}
+
+/// Show recorded bottle snapshots, newest first
+pub fn run(format: OutputFormat) -> Result<()> {
+    let history = HistoryEntry::load_all();
+    let entries: Vec<IndexedEntry> = history
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, entry)| IndexedEntry { index, entry })
+        .collect();
+
+    if format.is_json() {
+        return ui::print_json("history", &HistoryReport { entries });
+    }
+
+    if entries.is_empty() {
+        println!("{}", style("No history recorded yet.").dim());
+        println!();
+        println!(
+            "History is recorded by {}, {} and {}.",
+            style("bottle install").cyan(),
+            style("bottle update").cyan(),
+            style("bottle switch").cyan()
+        );
+        return Ok(());
+    }
+
+    println!("{}:", style("Bottle history").bold());
+    for IndexedEntry { index, entry } in &entries {
+        let current = if *index == 0 {
+            style("(current)").green().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:>3}  {}  {:<9} {} {} {}",
+            index,
+            style(entry.timestamp.format("%Y-%m-%d %H:%M")).dim(),
+            entry.operation.name(),
+            style(&entry.bottle).cyan(),
+            entry.bottle_version,
+            current
+        );
+
+        let tools: Vec<String> = entry
+            .tools
+            .iter()
+            .map(|(name, version)| format!("{} {}", name, version))
+            .collect();
+        if !tools.is_empty() {
+            println!("       {}", style(tools.join(", ")).dim());
+        }
+    }
+    println!();
+    println!(
+        "Roll back with {}",
+        style("bottle rollback [<n>|<version>]").cyan()
+    );
+
+    Ok(())
+}
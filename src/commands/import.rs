@@ -0,0 +1,50 @@
+use super::common::fetch_shared_bottle;
+use crate::error::{BottleError, Result};
+use crate::manifest::state::BottleState;
+use crate::ui;
+use console::style;
+
+/// Save a shared bottle (manifest URL, gh: path or git repository) as a bespoke
+/// bottle without installing it
+pub fn run(source: &str) -> Result<()> {
+    // 1. Fetch and check the manifest
+    let shared = fetch_shared_bottle(source)?.ok_or_else(|| {
+        BottleError::Other(format!(
+            "'{}' is not a bottle source. Use an https:// manifest URL, \
+             gh:org/repo[/path][#ref], or git+<url>[//path][#ref].",
+            source
+        ))
+    })?;
+
+    // 2. Save it under ~/.bottle/bottles/<name>
+    shared.save()?;
+
+    // 3. Report
+    let name = &shared.manifest.name;
+    ui::print_success(&format!(
+        "Imported bespoke bottle {} {} from {}",
+        name, shared.manifest.version, shared.source
+    ));
+    if let Some(path) = BottleState::bottle_path(name) {
+        println!();
+        println!(
+            "Location: {}",
+            style(path.join("manifest.json").display().to_string()).cyan()
+        );
+    }
+    println!();
+    println!("{}:", style("Next steps").bold());
+    println!(
+        "  {} - Install it (or {} from another bottle)",
+        style(format!("bottle install {}", name)).cyan(),
+        style(format!("bottle switch {}", name)).cyan()
+    );
+    println!(
+        "  {} - Fetch it again from {} later",
+        style("bottle update").cyan(),
+        shared.source
+    );
+    println!();
+
+    Ok(())
+}
@@ -1,23 +1,57 @@
-use super::common::{check_prerequisites, fetch_or_load_manifest};
+use super::common::{
+    check_prerequisites, fetch_or_load_manifest, fetch_shared_bottle, install_tool_jobs, load_lock,
+    lock_path, record_history, recorded_source, require_dry_run_for_json, resolve_versions,
+    write_lock,
+};
+use super::transaction::{run_strict, Transaction};
 use crate::error::{BottleError, Result};
-use crate::fetch::fetch_tool_definition;
-use crate::install;
-use crate::manifest::bottle::BottleManifest;
+use crate::install::{self, log, parallel::Job};
+use crate::integrate::Platform;
+use crate::manifest::bottle::{BottleManifest, BrewPolicy, CustomToolDef};
+use crate::manifest::history::Operation;
+use crate::manifest::project::Project;
 use crate::manifest::state::{BottleState, CustomInstallMethod, CustomToolState, Mode, ToolState};
-use crate::ui;
+use crate::ui::{self, OutputFormat};
+use crate::version;
 use chrono::Utc;
 use console::style;
+use serde::Serialize;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::process::Command;
 
-/// Install a bottle (stable, edge, or bespoke)
+/// Install a bottle (stable, edge, bespoke, or the project's bottle.json)
 pub fn run(
-    bottle: &str,
+    bottle: Option<&str>,
     manifest_path: Option<&std::path::Path>,
     yes: bool,
     dry_run: bool,
     force: bool,
+    strict: bool,
+    format: OutputFormat,
 ) -> Result<()> {
+    require_dry_run_for_json(format, dry_run)?;
+
+    // A project's bottle.json is installed unless a bottle or manifest is named
+    let project = if bottle.is_none() && manifest_path.is_none() {
+        Project::discover()
+    } else {
+        None
+    };
+    let project_manifest = project.as_ref().map(|p| p.manifest_path());
+    let manifest_path = manifest_path.or(project_manifest.as_deref());
+
+    // A shared bottle (manifest URL, gh: path or git repository) installs under the
+    // name in its manifest
+    let shared = match (bottle, manifest_path) {
+        (Some(spec), None) => fetch_shared_bottle(spec)?,
+        _ => None,
+    };
+    let bottle = match &shared {
+        Some(shared) => shared.manifest.name.as_str(),
+        None => bottle.unwrap_or("stable"),
+    };
+
     // 1. Check if already installed (skip if --force or using explicit manifest)
     if !force && manifest_path.is_none() {
         if let Some(state) = BottleState::load() {
@@ -36,17 +70,40 @@ pub fn run(
         }
     }
 
-    // 2. Fetch manifest (explicit path, local bespoke, or remote curated)
-    let spinner = ui::spinner("Fetching bottle manifest...");
-    let manifest = fetch_or_load_manifest(bottle, manifest_path)?;
-    spinner.finish_and_clear();
+    // 2. Fetch manifest (shared, explicit path, local bespoke, or remote curated).
+    // A shared bottle is saved as a bespoke one once its tools are installed.
+    let (manifest, source) = match &shared {
+        Some(shared) => {
+            shared.check()?;
+            (shared.manifest.clone(), Some(shared.source.to_string()))
+        }
+        None => {
+            let spinner = ui::spinner("Fetching bottle manifest...");
+            let manifest = fetch_or_load_manifest(bottle, manifest_path)?;
+            spinner.finish_and_clear();
+            let source = manifest_path.is_none().then(|| recorded_source(bottle));
+            (manifest, source.flatten())
+        }
+    };
+
+    // "latest" installs what the lock file recorded, or else the newest release
+    let lock_path = lock_path(bottle, manifest_path);
+    let locked = lock_path.as_deref().and_then(load_lock);
+    let manifest = resolve_versions(&manifest, locked.as_ref());
+    if let (Some(path), false) = (&project_manifest, format.is_json()) {
+        ui::print_info(&format!("Using project bottle {}", path.display()));
+    }
 
     // 3. Check prerequisites
     check_prerequisites(&manifest)?;
 
     // 4. Show what will be installed (or would be installed for dry-run)
     if dry_run {
-        show_dry_run_plan(&manifest);
+        let plan = build_install_plan(&manifest);
+        if format.is_json() {
+            return ui::print_json("install", &plan);
+        }
+        show_dry_run_plan(&plan);
         return Ok(());
     }
 
@@ -59,38 +116,54 @@ pub fn run(
 
     println!();
 
-    // 6. Install tools (binaries + MCP)
-    let tool_states = install_tools(&manifest)?;
-
-    // 7. Install bespoke MCP servers (if any)
-    install_mcp_servers(&manifest)?;
+    // 6-10. Install everything and write state (all-or-nothing with --strict)
+    let tx = strict.then(|| Transaction::begin(&manifest.name, BottleState::load().as_ref()));
+    run_strict(tx, |mut tx| {
+        // 6. Install tools (binaries + MCP)
+        let tool_states = install_tools(&manifest, tx.as_deref_mut())?;
 
-    // 8. Build AGENTS.md snippet (agent applies it later)
-    let snippet = save_agents_md_snippet(&manifest)?;
+        // 7. Install bespoke MCP servers (if any)
+        install_mcp_servers(&manifest, tx.as_deref_mut())?;
 
-    // 9. Install custom tools (if any)
-    let custom_tool_states = install_custom_tools(&manifest)?;
+        // 8. Build AGENTS.md snippet (agent applies it later)
+        let snippet = save_agents_md_snippet(&manifest)?;
 
-    // 10. Write state (includes snippet if present)
-    let state = BottleState {
-        bottle: manifest.name.clone(),
-        bottle_version: manifest.version.clone(),
-        installed_at: Utc::now(),
-        tools: tool_states,
-        mode: Mode::Managed,
-        integrations: HashMap::new(),
-        custom_tools: custom_tool_states,
-    };
-    state
-        .save()
-        .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+        // 9. Install custom tools (if any)
+        let custom_tool_states = install_custom_tools(&manifest, tx)?;
+        if let Some(shared) = &shared {
+            shared.save()?;
+        }
 
-    // Save snippet alongside state if present
-    if let Some(snippet_content) = &snippet {
+        // 10. Write state (includes snippet if present)
+        let state = BottleState {
+            bottle: manifest.name.clone(),
+            bottle_version: manifest.version.clone(),
+            installed_at: Utc::now(),
+            tools: tool_states,
+            mode: Mode::Managed,
+            integrations: HashMap::new(),
+            custom_tools: custom_tool_states,
+            source,
+        };
         state
-            .save_snippet(snippet_content)
-            .map_err(|e| BottleError::Other(format!("Failed to save AGENTS.md snippet: {}", e)))?;
-    }
+            .save()
+            .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+
+        // Save snippet alongside state if present
+        if let Some(snippet_content) = &snippet {
+            state.save_snippet(snippet_content).map_err(|e| {
+                BottleError::Other(format!("Failed to save AGENTS.md snippet: {}", e))
+            })?;
+        }
+
+        if let Some(path) = &lock_path {
+            write_lock(path, &state)?;
+        }
+
+        record_history(Operation::Install, &state);
+
+        Ok(())
+    })?;
 
     // 11. Show success
     show_success(&manifest);
@@ -120,53 +193,221 @@ fn show_install_plan(manifest: &BottleManifest) {
     println!();
 }
 
+/// What installing a tool would do, given the currently installed version
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ToolAction {
+    Install,
+    Current,
+    Upgrade,
+    Downgrade,
+    Update,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedTool {
+    name: String,
+    version: String,
+    /// Version detected on this machine, if any
+    installed: Option<String>,
+    action: ToolAction,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedMcpServer {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    scope: String,
+    /// Env entries that reference runtime variables (KEY=${VAR})
+    required_env: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedSnippet {
+    sections: Vec<String>,
+    snippets_url: Option<String>,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedCustomTool {
+    name: String,
+    version: String,
+    methods: Vec<&'static str>,
+    verify: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedIntegration {
+    platform: &'static str,
+    detected: bool,
+}
+
+/// Dry-run install plan, shared by the human and JSON renderers
+#[derive(Debug, Serialize)]
+struct InstallPlan {
+    bottle: String,
+    version: String,
+    description: String,
+    tools: Vec<PlannedTool>,
+    plugins: Vec<String>,
+    mcp_servers: Vec<PlannedMcpServer>,
+    agents_md: Option<PlannedSnippet>,
+    custom_tools: Vec<PlannedCustomTool>,
+    integrations: Vec<PlannedIntegration>,
+}
+
+/// Build the dry-run plan by comparing the manifest with what is on this machine
+fn build_install_plan(manifest: &BottleManifest) -> InstallPlan {
+    let mut tools: Vec<PlannedTool> = manifest
+        .tools
+        .iter()
+        .map(|(name, target_version)| {
+            let installed = get_tool_version(name);
+            let action = match &installed {
+                None => ToolAction::Install,
+                Some(v) if version::satisfies(target_version, v) => ToolAction::Current,
+                Some(v) => match version::compare(v, target_version) {
+                    Some(Ordering::Less) => ToolAction::Upgrade,
+                    Some(Ordering::Greater) => ToolAction::Downgrade,
+                    _ => ToolAction::Update,
+                },
+            };
+            PlannedTool {
+                name: name.clone(),
+                version: target_version.clone(),
+                installed,
+                action,
+            }
+        })
+        .collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut mcp_servers: Vec<PlannedMcpServer> = manifest
+        .mcp_servers
+        .iter()
+        .map(|(name, server)| {
+            let mut required_env: Vec<String> = server
+                .env
+                .iter()
+                .filter(|(_, value)| value.contains("${"))
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            required_env.sort();
+            PlannedMcpServer {
+                name: name.clone(),
+                command: server.command.clone(),
+                args: server.args.clone(),
+                scope: server.scope.clone(),
+                required_env,
+            }
+        })
+        .collect();
+    mcp_servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let agents_md = manifest
+        .agents_md
+        .as_ref()
+        .filter(|a| !a.sections.is_empty() || a.snippets_url.is_some())
+        .map(|a| PlannedSnippet {
+            sections: a.sections.iter().map(|s| s.heading.clone()).collect(),
+            snippets_url: a.snippets_url.clone(),
+            path: format!("~/.bottle/bottles/{}/agents-md-snippet", manifest.name),
+        });
+
+    let mut custom_tools: Vec<PlannedCustomTool> = manifest
+        .custom_tools
+        .iter()
+        .map(|(name, tool)| PlannedCustomTool {
+            name: name.clone(),
+            version: tool.version.clone(),
+            methods: [
+                tool.install.brew.as_ref().map(|_| "brew"),
+                tool.install.cargo.as_ref().map(|_| "cargo"),
+                tool.install.npm.as_ref().map(|_| "npm"),
+                tool.install.binary_url.as_ref().map(|_| "binary"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            verify: tool.verify.clone(),
+        })
+        .collect();
+    custom_tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let integrations = vec![
+        PlannedIntegration {
+            platform: Platform::ClaudeCode.key(),
+            detected: crate::integrate::claude_code::is_detected(),
+        },
+        PlannedIntegration {
+            platform: Platform::OpenCode.key(),
+            detected: crate::integrate::opencode::is_detected(),
+        },
+        PlannedIntegration {
+            platform: Platform::Codex.key(),
+            detected: crate::integrate::codex::is_detected(),
+        },
+    ];
+
+    InstallPlan {
+        bottle: manifest.name.clone(),
+        version: manifest.version.clone(),
+        description: manifest.description.clone(),
+        tools,
+        plugins: manifest.plugins.clone(),
+        mcp_servers,
+        agents_md,
+        custom_tools,
+        integrations,
+    }
+}
+
 /// Display the dry-run plan showing what would be installed
-fn show_dry_run_plan(manifest: &BottleManifest) {
+fn show_dry_run_plan(plan: &InstallPlan) {
     println!();
     println!("{}", style("[DRY RUN]").yellow().bold());
     println!(
         "Would install bottle {} ({}):",
-        style(&manifest.name).cyan(),
-        &manifest.version
+        style(&plan.bottle).cyan(),
+        &plan.version
     );
-    println!("{}", style(&manifest.description).dim());
+    println!("{}", style(&plan.description).dim());
     println!();
 
     // Show tools with installation status
     println!("{}:", style("Tools").bold());
-    let mut tools: Vec<_> = manifest.tools.iter().collect();
-    tools.sort_by_key(|(name, _)| *name);
-    for (name, target_version) in &tools {
-        let current = get_tool_version(name);
-        match current {
-            Some(installed_ver) if installed_ver == **target_version => {
+    for tool in &plan.tools {
+        match (&tool.action, &tool.installed) {
+            (ToolAction::Current, _) => {
                 println!(
                     "  {:<12} {} {}",
-                    name,
+                    tool.name,
                     style("current").green(),
-                    style(target_version).dim()
+                    style(&tool.version).dim()
+                );
+            }
+            (ToolAction::Install, _) | (_, None) => {
+                println!(
+                    "  {:<12} {} {}",
+                    tool.name,
+                    style("install").yellow(),
+                    tool.version
                 );
             }
-            Some(installed_ver) => {
-                let arrow = match compare_versions(&installed_ver, target_version) {
-                    "upgrade" => style("↑").green(),
-                    "downgrade" => style("↓").red(),
+            (action, Some(installed_ver)) => {
+                let arrow = match action {
+                    ToolAction::Upgrade => style("↑").green(),
+                    ToolAction::Downgrade => style("↓").red(),
                     _ => style("→").yellow(),
                 };
                 println!(
                     "  {:<12} {} {} {}",
-                    name,
+                    tool.name,
                     arrow,
-                    style(&installed_ver).dim(),
-                    target_version
-                );
-            }
-            None => {
-                println!(
-                    "  {:<12} {} {}",
-                    name,
-                    style("install").yellow(),
-                    target_version
+                    style(installed_ver).dim(),
+                    tool.version
                 );
             }
         }
@@ -174,24 +415,22 @@ fn show_dry_run_plan(manifest: &BottleManifest) {
     println!();
 
     // Show plugins available via integrate
-    if !manifest.plugins.is_empty() {
+    if !plan.plugins.is_empty() {
         println!(
             "{} {}:",
             style("Plugins").bold(),
             style("(via bottle integrate)").dim()
         );
-        for plugin in &manifest.plugins {
+        for plugin in &plan.plugins {
             println!("  {}", plugin);
         }
         println!();
     }
 
     // Show bespoke MCP servers
-    if !manifest.mcp_servers.is_empty() {
+    if !plan.mcp_servers.is_empty() {
         println!("{}:", style("MCP Servers").bold());
-        let mut servers: Vec<_> = manifest.mcp_servers.iter().collect();
-        servers.sort_by_key(|(name, _)| *name);
-        for (name, server) in servers {
+        for server in &plan.mcp_servers {
             let args_str = if server.args.is_empty() {
                 String::new()
             } else {
@@ -199,66 +438,42 @@ fn show_dry_run_plan(manifest: &BottleManifest) {
             };
             println!(
                 "  {:<20} {} {}{}",
-                name,
+                server.name,
                 style(&server.command).dim(),
                 style(format!("[{}]", server.scope)).dim(),
                 style(&args_str).dim()
             );
             // Show env vars that need to be set
-            for (key, value) in &server.env {
-                if value.contains("${") {
-                    println!(
-                        "    {} {}",
-                        style("env:").dim(),
-                        style(format!("{}={}", key, value)).yellow()
-                    );
-                }
+            for env in &server.required_env {
+                println!("    {} {}", style("env:").dim(), style(env).yellow());
             }
         }
         println!();
     }
 
     // Show AGENTS.md snippet info
-    if let Some(agents_config) = &manifest.agents_md {
-        if !agents_config.sections.is_empty() || agents_config.snippets_url.is_some() {
-            println!("{}:", style("AGENTS.md Snippet").bold());
-            println!("  {}", style("(saved for agent to apply)").dim());
-            for section in &agents_config.sections {
-                println!("  {} {}", style("Section:").dim(), &section.heading);
-            }
-            if let Some(url) = &agents_config.snippets_url {
-                println!("  {} {}", style("Snippets URL:").dim(), style(url).cyan());
-            }
-            println!(
-                "  {} ~/.bottle/bottles/{}/agents-md-snippet",
-                style("Saved to:").dim(),
-                manifest.name
-            );
-            println!();
+    if let Some(snippet) = &plan.agents_md {
+        println!("{}:", style("AGENTS.md Snippet").bold());
+        println!("  {}", style("(saved for agent to apply)").dim());
+        for heading in &snippet.sections {
+            println!("  {} {}", style("Section:").dim(), heading);
         }
+        if let Some(url) = &snippet.snippets_url {
+            println!("  {} {}", style("Snippets URL:").dim(), style(url).cyan());
+        }
+        println!("  {} {}", style("Saved to:").dim(), snippet.path);
+        println!();
     }
 
     // Show custom tools
-    if !manifest.custom_tools.is_empty() {
+    if !plan.custom_tools.is_empty() {
         println!("{}:", style("Custom Tools").bold());
-        let mut tools: Vec<_> = manifest.custom_tools.iter().collect();
-        tools.sort_by_key(|(name, _)| *name);
-        for (name, tool) in tools {
-            let methods: Vec<&str> = [
-                tool.install.brew.as_ref().map(|_| "brew"),
-                tool.install.cargo.as_ref().map(|_| "cargo"),
-                tool.install.npm.as_ref().map(|_| "npm"),
-                tool.install.binary_url.as_ref().map(|_| "binary"),
-            ]
-            .into_iter()
-            .flatten()
-            .collect();
-
+        for tool in &plan.custom_tools {
             println!(
                 "  {:<20} {} {}",
-                name,
+                tool.name,
                 style(&tool.version).dim(),
-                style(format!("[{}]", methods.join(", "))).dim()
+                style(format!("[{}]", tool.methods.join(", "))).dim()
             );
             if let Some(verify) = &tool.verify {
                 println!("    {} {}", style("verify:").dim(), style(verify).dim());
@@ -274,31 +489,27 @@ fn show_dry_run_plan(manifest: &BottleManifest) {
         style("(optional, run after install)").dim()
     );
     println!();
-    show_claude_code_integration();
-    show_opencode_integration();
-    show_codex_integration();
+    for integration in &plan.integrations {
+        match integration.platform {
+            "claude_code" => show_claude_code_integration(integration.detected),
+            "opencode" => show_opencode_integration(integration.detected),
+            _ => show_codex_integration(integration.detected),
+        }
+    }
     println!();
 
     // Show state changes
     println!("{}:", style("State changes").bold());
     println!(
         "  Create ~/.bottle/bottles/{}/state.json with:",
-        manifest.name
+        plan.bottle
     );
-    println!("    bottle: {}", manifest.name);
-    println!("    version: {}", manifest.version);
+    println!("    bottle: {}", plan.bottle);
+    println!("    version: {}", plan.version);
     println!("    mode: managed");
-    println!("  Set active bottle: ~/.bottle/active → {}", manifest.name);
-    if manifest
-        .agents_md
-        .as_ref()
-        .map(|a| !a.sections.is_empty() || a.snippets_url.is_some())
-        .unwrap_or(false)
-    {
-        println!(
-            "  Save AGENTS.md snippet: ~/.bottle/bottles/{}/agents-md-snippet",
-            manifest.name
-        );
+    println!("  Set active bottle: ~/.bottle/active → {}", plan.bottle);
+    if let Some(snippet) = &plan.agents_md {
+        println!("  Save AGENTS.md snippet: {}", snippet.path);
     }
     println!();
 
@@ -306,8 +517,7 @@ fn show_dry_run_plan(manifest: &BottleManifest) {
     println!();
 }
 
-fn show_claude_code_integration() {
-    let detected = crate::integrate::claude_code::is_detected();
+fn show_claude_code_integration(detected: bool) {
     if detected {
         println!(
             "  {} {}",
@@ -327,8 +537,7 @@ fn show_claude_code_integration() {
     println!();
 }
 
-fn show_opencode_integration() {
-    let detected = crate::integrate::opencode::is_detected();
+fn show_opencode_integration(detected: bool) {
     if detected {
         println!(
             "  {} {}",
@@ -348,8 +557,7 @@ fn show_opencode_integration() {
     println!();
 }
 
-fn show_codex_integration() {
-    let detected = crate::integrate::codex::is_detected();
+fn show_codex_integration(detected: bool) {
     if detected {
         println!(
             "  {} {}",
@@ -364,30 +572,6 @@ fn show_codex_integration() {
     }
 }
 
-/// Compare two version strings, returns "upgrade", "downgrade", or "update"
-fn compare_versions(current: &str, target: &str) -> &'static str {
-    // Simple semver comparison - split by dots and compare numerically
-    let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse().ok()).collect() };
-
-    let current_parts = parse(current);
-    let target_parts = parse(target);
-
-    for (c, t) in current_parts.iter().zip(target_parts.iter()) {
-        if t > c {
-            return "upgrade";
-        } else if t < c {
-            return "downgrade";
-        }
-    }
-
-    // If we get here, compare lengths (e.g., 1.0 vs 1.0.1)
-    match target_parts.len().cmp(&current_parts.len()) {
-        std::cmp::Ordering::Greater => "upgrade",
-        std::cmp::Ordering::Less => "downgrade",
-        std::cmp::Ordering::Equal => "update", // shouldn't happen if versions are equal
-    }
-}
-
 /// Get installed version of a tool, or None if not installed
 fn get_tool_version(tool: &str) -> Option<String> {
     let binary = match tool {
@@ -437,12 +621,16 @@ fn is_mcp_registered(name: &str) -> bool {
         .unwrap_or(false)
 }
 
-/// Install all tools from the manifest
+/// Install all tools from the manifest, several at a time
 /// AIDEV-NOTE: Intentionally continues on failure and returns Ok with partial results.
 /// State tracks what succeeded. User sees warnings for failures and can retry.
 /// This is a design decision to avoid leaving users in a broken state when one
-/// tool fails but others succeed. Consider adding --strict flag if needed later.
-fn install_tools(manifest: &BottleManifest) -> Result<HashMap<String, ToolState>> {
+/// tool fails but others succeed. With --strict (`tx` is Some) the first failure
+/// is returned instead so the caller can roll back.
+fn install_tools(
+    manifest: &BottleManifest,
+    tx: Option<&mut Transaction>,
+) -> Result<HashMap<String, ToolState>> {
     let mut states = HashMap::new();
     let mut failures: Vec<(String, BottleError)> = Vec::new();
 
@@ -451,38 +639,15 @@ fn install_tools(manifest: &BottleManifest) -> Result<HashMap<String, ToolState>
     let mut tools: Vec<_> = manifest.tools.iter().collect();
     tools.sort_by_key(|(name, _)| *name);
 
-    for (tool_name, version) in tools {
-        print!("  {:<12} {} ", tool_name, style(version).dim());
-
-        // Fetch tool definition
-        let tool_def = match fetch_tool_definition(tool_name) {
-            Ok(def) => def,
-            Err(e) => {
-                println!("{}", style("failed").red());
-                failures.push((tool_name.clone(), e));
-                continue;
-            }
-        };
-
-        // Install the tool
-        match install::install_tool(&tool_def, version) {
-            Ok(method) => {
-                println!("{}", style("installed").green());
-                states.insert(
-                    tool_name.clone(),
-                    ToolState {
-                        version: version.clone(),
-                        installed_at: Utc::now(),
-                        method,
-                    },
-                );
-            }
-            Err(e) => {
-                println!("{}", style("failed").red());
-                failures.push((tool_name.clone(), e));
-            }
-        }
-    }
+    let jobs: Vec<Job> = tools
+        .into_iter()
+        .map(|(tool, version)| Job {
+            tool: tool.clone(),
+            version: version.clone(),
+            done: "installed",
+        })
+        .collect();
+    install_tool_jobs(&jobs, manifest.brew_policy, &mut states, &mut failures, tx)?;
 
     println!();
 
@@ -546,8 +711,11 @@ fn save_agents_md_snippet(manifest: &BottleManifest) -> Result<Option<String>> {
     }
 }
 
-/// Install custom tools from the manifest
-fn install_custom_tools(manifest: &BottleManifest) -> Result<HashMap<String, CustomToolState>> {
+/// Install custom tools from the manifest (stops at the first failure with --strict)
+fn install_custom_tools(
+    manifest: &BottleManifest,
+    mut tx: Option<&mut Transaction>,
+) -> Result<HashMap<String, CustomToolState>> {
     let mut installed: HashMap<String, CustomToolState> = HashMap::new();
 
     if manifest.custom_tools.is_empty() {
@@ -563,8 +731,15 @@ fn install_custom_tools(manifest: &BottleManifest) -> Result<HashMap<String, Cus
     for (name, tool) in tools {
         print!("  {:<20} {} ", name, style(&tool.version).dim());
 
-        match install_custom_tool(name, tool) {
+        let preexisting = tx.is_some() && which::which(name).is_ok();
+        match log::capture(name, true, || {
+            install_custom_tool(name, tool, manifest.brew_policy)
+        }) {
             Ok(method) => {
+                if let Some(tx) = tx.as_deref_mut() {
+                    tx.record_custom_tool(name, tool, method.clone(), preexisting);
+                }
+
                 let method_name = match method {
                     CustomInstallMethod::Brew => "brew",
                     CustomInstallMethod::Cargo => "cargo",
@@ -601,6 +776,9 @@ fn install_custom_tools(manifest: &BottleManifest) -> Result<HashMap<String, Cus
             }
             Err(e) => {
                 println!("{}", style("failed").red());
+                if tx.is_some() {
+                    return Err(e);
+                }
                 failures.push((name.clone(), e));
             }
         }
@@ -623,44 +801,19 @@ fn install_custom_tools(manifest: &BottleManifest) -> Result<HashMap<String, Cus
 }
 
 /// Install a single custom tool, trying methods in order
-fn install_custom_tool(
+pub(super) fn install_custom_tool(
     name: &str,
-    tool: &crate::manifest::bottle::CustomToolDef,
+    tool: &CustomToolDef,
+    brew_policy: BrewPolicy,
 ) -> Result<CustomInstallMethod> {
     let install = &tool.install;
 
-    // Try brew first
-    // Note: Versioned formulas (formula@version) only work for some packages.
-    // Tap formulas (org/tap/formula) don't support the @version syntax.
+    // Try brew first (pinned to the requested version, see install::brew)
     if let Some(formula) = &install.brew {
         if which::which("brew").is_ok() {
-            let has_version = !tool.version.is_empty() && tool.version != "latest";
-            let is_tap_formula = formula.contains('/');
-
-            // Warn if trying to version a tap formula (unlikely to work)
-            if has_version && is_tap_formula {
-                eprintln!(
-                    "Warning: Tap formula '{}' may not support @{} syntax. Consider using 'latest'.",
-                    formula, tool.version
-                );
-            }
-
-            let version_formula = if has_version {
-                format!("{}@{}", formula, tool.version)
-            } else {
-                formula.clone()
-            };
-
-            let status = Command::new("brew")
-                .args(["install", &version_formula])
-                .status()
-                .map_err(|e| BottleError::InstallError {
-                    tool: name.to_string(),
-                    reason: format!("brew install failed: {}", e),
-                })?;
-
-            if status.success() {
-                return Ok(CustomInstallMethod::Brew);
+            match install::brew::install(None, formula, &tool.version, brew_policy) {
+                Ok(_) => return Ok(CustomInstallMethod::Brew),
+                Err(e) => ui::print_warning(&format!("{}, trying other methods", e)),
             }
         }
     }
@@ -675,7 +828,7 @@ fn install_custom_tool(
                 args.push(&version_arg);
             }
 
-            let status = Command::new("cargo").args(&args).status().map_err(|e| {
+            let status = log::run(Command::new("cargo").args(&args)).map_err(|e| {
                 BottleError::InstallError {
                     tool: name.to_string(),
                     reason: format!("cargo install failed: {}", e),
@@ -697,9 +850,7 @@ fn install_custom_tool(
                 format!("{}@{}", package, tool.version)
             };
 
-            let status = Command::new("npm")
-                .args(["install", "-g", &package_spec])
-                .status()
+            let status = log::run(Command::new("npm").args(["install", "-g", &package_spec]))
                 .map_err(|e| BottleError::InstallError {
                     tool: name.to_string(),
                     reason: format!("npm install failed: {}", e),
@@ -712,9 +863,8 @@ fn install_custom_tool(
     }
 
     // Try binary_url
-    if let Some(url_template) = &install.binary_url {
-        let url = expand_binary_url(url_template);
-        install_from_binary_url(name, &url)?;
+    if install.binary_url.is_some() {
+        install::binary::install(name, install)?;
         return Ok(CustomInstallMethod::Binary);
     }
 
@@ -724,115 +874,62 @@ fn install_custom_tool(
     })
 }
 
-/// Expand placeholders in binary URL
-/// Supported: {arch}, {os}, {platform}, {arm64}
-fn expand_binary_url(url: &str) -> String {
-    let arch = std::env::consts::ARCH;
-    let os = std::env::consts::OS;
-
-    // Alternative arch name for Apple Silicon (many releases use "arm64" instead of "aarch64")
-    let arm64_name = match arch {
-        "aarch64" => "arm64",
-        _ => arch,
-    };
-
-    let os_name = match os {
-        "macos" => "darwin",
-        "linux" => "linux",
-        "windows" => "windows",
-        _ => os,
-    };
-
-    url.replace("{arch}", arch)
-        .replace("{arm64}", arm64_name)
-        .replace("{os}", os_name)
-        .replace("{platform}", &format!("{}-{}", os_name, arch))
-}
-
-/// Install a tool from a binary URL (raw binary only, no archive support)
-fn install_from_binary_url(name: &str, url: &str) -> Result<()> {
-    use std::time::Duration;
-
-    // Enforce HTTPS for security
-    if !url.starts_with("https://") {
-        return Err(BottleError::InstallError {
-            tool: name.to_string(),
-            reason: format!("Binary URL must use HTTPS: {}", url),
-        });
-    }
-
-    // Build client with timeout
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(120))
-        .build()
-        .map_err(|e| BottleError::InstallError {
-            tool: name.to_string(),
-            reason: format!("Failed to create HTTP client: {}", e),
-        })?;
-
-    // Download the binary
-    let response = client
-        .get(url)
-        .send()
-        .map_err(|e| BottleError::InstallError {
-            tool: name.to_string(),
-            reason: format!("Failed to download from {}: {}", url, e),
-        })?;
-
-    if !response.status().is_success() {
-        return Err(BottleError::InstallError {
-            tool: name.to_string(),
-            reason: format!("HTTP {} from {}", response.status(), url),
-        });
-    }
-
-    let bytes = response.bytes().map_err(|e| BottleError::InstallError {
-        tool: name.to_string(),
-        reason: format!("Failed to read response: {}", e),
-    })?;
-
-    // Determine install path (~/.local/bin)
-    let bin_dir = dirs::home_dir()
-        .map(|h| h.join(".local").join("bin"))
-        .ok_or_else(|| BottleError::InstallError {
-            tool: name.to_string(),
-            reason: "Could not determine home directory".to_string(),
-        })?;
-
-    std::fs::create_dir_all(&bin_dir).map_err(|e| BottleError::InstallError {
+/// Uninstall a custom tool using the method it was installed with
+pub(super) fn uninstall_custom_tool(
+    name: &str,
+    tool: &CustomToolDef,
+    method: &CustomInstallMethod,
+) -> Result<()> {
+    let install = &tool.install;
+    let missing = |field: &str| BottleError::UninstallError {
         tool: name.to_string(),
-        reason: format!("Failed to create bin directory: {}", e),
-    })?;
-
-    let bin_path = bin_dir.join(name);
+        reason: format!("No {} package recorded in manifest", field),
+    };
 
-    // Write the binary
-    std::fs::write(&bin_path, &bytes).map_err(|e| BottleError::InstallError {
-        tool: name.to_string(),
-        reason: format!("Failed to write binary: {}", e),
-    })?;
+    match method {
+        CustomInstallMethod::Brew => {
+            install::brew::uninstall(install.brew.as_ref().ok_or_else(|| missing("brew"))?)
+        }
+        CustomInstallMethod::Cargo => {
+            install::cargo::uninstall(install.cargo.as_ref().ok_or_else(|| missing("cargo"))?)
+        }
+        CustomInstallMethod::Npm => {
+            let package = install.npm.as_ref().ok_or_else(|| missing("npm"))?;
+            let status =
+                log::run(Command::new("npm").args(["uninstall", "-g", package])).map_err(|e| {
+                    BottleError::UninstallError {
+                        tool: name.to_string(),
+                        reason: format!("npm uninstall failed: {}", e),
+                    }
+                })?;
 
-    // Make executable
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        if let Err(e) = std::fs::set_permissions(&bin_path, std::fs::Permissions::from_mode(0o755))
-        {
-            eprintln!(
-                "Warning: could not set executable permissions on {}: {}",
-                bin_path.display(),
-                e
-            );
+            if status.success() {
+                Ok(())
+            } else {
+                Err(BottleError::UninstallError {
+                    tool: name.to_string(),
+                    reason: format!("npm uninstall exited with code {}", status),
+                })
+            }
+        }
+        CustomInstallMethod::Binary => {
+            let bin_path =
+                install::binary::bin_path(name).ok_or_else(|| BottleError::UninstallError {
+                    tool: name.to_string(),
+                    reason: "Could not determine home directory".to_string(),
+                })?;
+            std::fs::remove_file(&bin_path).map_err(|e| BottleError::UninstallError {
+                tool: name.to_string(),
+                reason: format!("Failed to remove {}: {}", bin_path.display(), e),
+            })
         }
     }
-
-    Ok(())
 }
 
 /// Run a verification command after tool installation.
 /// Note: Commands are split on whitespace, so quoted arguments are not supported.
 /// Keep verify commands simple (e.g., "mytool --version").
-fn run_verify_command(verify: &str) -> Result<()> {
+pub(super) fn run_verify_command(verify: &str) -> Result<()> {
     let parts: Vec<&str> = verify.split_whitespace().collect();
     if parts.is_empty() {
         return Ok(());
@@ -855,8 +952,8 @@ fn run_verify_command(verify: &str) -> Result<()> {
     }
 }
 
-/// Install bespoke MCP servers from the manifest
-fn install_mcp_servers(manifest: &BottleManifest) -> Result<()> {
+/// Install bespoke MCP servers from the manifest (stops at the first failure with --strict)
+fn install_mcp_servers(manifest: &BottleManifest, mut tx: Option<&mut Transaction>) -> Result<()> {
     if manifest.mcp_servers.is_empty() {
         return Ok(());
     }
@@ -876,12 +973,19 @@ fn install_mcp_servers(manifest: &BottleManifest) -> Result<()> {
         print!("  {:<20} ", name);
 
         // Register with Claude Code
+        let preexisting = tx.is_some() && install::mcp::is_registered(name);
         match install::mcp::register_bespoke(name, server) {
             Ok(()) => {
                 println!("{}", style("registered").green());
+                if let Some(tx) = tx.as_deref_mut() {
+                    tx.record_mcp_server(name, preexisting);
+                }
             }
             Err(e) => {
                 println!("{}", style("failed").red());
+                if tx.is_some() {
+                    return Err(e);
+                }
                 failures.push((name.clone(), e));
             }
         }
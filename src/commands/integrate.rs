@@ -83,11 +83,7 @@ fn show_integrations(state: &BottleState) -> Result<()> {
                 style("not found").dim()
             };
 
-            let hint = if detected {
-                format!("({})", detection.detection_hint)
-            } else {
-                format!("({})", detection.detection_hint)
-            };
+            let hint = format!("({})", detection.detection_hint);
 
             println!(
                 "  {:<12} {} {}",
@@ -319,7 +315,7 @@ fn describe_install_action(platform: Platform) -> &'static str {
 }
 
 /// Describe the remove action for a platform (for dry-run output)
-fn describe_remove_action(platform: Platform) -> &'static str {
+pub(super) fn describe_remove_action(platform: Platform) -> &'static str {
     match platform {
         Platform::ClaudeCode => "Remove all plugins: bottle, ba, superego, wm, oh-mcp, miranda",
         Platform::OpenCode => "Remove bottle ecosystem plugins from opencode.json",
@@ -1,62 +1,109 @@
 use crate::error::Result;
-use crate::fetch::{fetch_bottle_manifest, list_available_bottles};
+use crate::fetch::fetch_bottle_index;
 use crate::manifest::bottle::BottleManifest;
+use crate::ui::{self, OutputFormat};
 use console::style;
+use serde::Serialize;
 use std::fs;
 use std::path::PathBuf;
 
+/// A bottle available to install
+#[derive(Debug, Serialize)]
+struct BottleSummary {
+    name: String,
+    description: String,
+    /// Latest version (from the index for curated bottles, the manifest for bespoke)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<String>,
+    /// Deprecation notice, if the bottle is deprecated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deprecated: Option<String>,
+}
+
+/// Curated and bespoke bottles, shared by the human and JSON renderers
+#[derive(Debug, Serialize)]
+struct BottleList {
+    curated: Vec<BottleSummary>,
+    bespoke: Vec<BottleSummary>,
+}
+
 /// List available bottles (curated and bespoke)
-pub fn run() -> Result<()> {
-    // Fetch curated bottles
-    let curated = list_curated_bottles();
+pub fn run(format: OutputFormat) -> Result<()> {
+    let list = BottleList {
+        // Fetch curated bottles
+        curated: list_curated_bottles(),
+        // Find bespoke bottles
+        bespoke: list_bespoke_bottles(),
+    };
 
-    // Find bespoke bottles
-    let bespoke = list_bespoke_bottles();
+    if format.is_json() {
+        return ui::print_json("list", &list);
+    }
 
     // Display curated bottles
-    println!("{}:", style("Curated bottles (from GitHub)").bold());
-    if curated.is_empty() {
+    println!("{}:", style("Curated bottles (from registry)").bold());
+    if list.curated.is_empty() {
         println!("  {}", style("(none available)").dim());
     } else {
-        for (name, description) in &curated {
-            println!("  {:<12} {}", style(name).cyan(), style(description).dim());
+        for bottle in &list.curated {
+            println!(
+                "  {:<12} {:<12} {}",
+                style(&bottle.name).cyan(),
+                bottle.version.as_deref().unwrap_or(""),
+                style(&bottle.description).dim()
+            );
+            if let Some(notice) = &bottle.deprecated {
+                println!("  {:<12} {} {}", "", style("deprecated:").yellow(), notice);
+            }
         }
     }
     println!();
 
     // Display bespoke bottles
     println!("{}:", style("Bespoke bottles (local)").bold());
-    if bespoke.is_empty() {
+    if list.bespoke.is_empty() {
         println!("  {}", style("(none)").dim());
     } else {
-        for (name, description) in &bespoke {
-            println!("  {:<12} {}", style(name).cyan(), style(description).dim());
+        for bottle in &list.bespoke {
+            println!(
+                "  {:<12} {:<12} {}",
+                style(&bottle.name).cyan(),
+                bottle.version.as_deref().unwrap_or(""),
+                style(&bottle.description).dim()
+            );
         }
     }
 
     Ok(())
 }
 
-/// Fetch curated bottles with descriptions
-fn list_curated_bottles() -> Vec<(String, String)> {
-    let bottle_names = match list_available_bottles() {
-        Ok(names) => names,
-        Err(_) => return Vec::new(),
+/// Read curated bottles from the registry index (one fetch)
+fn list_curated_bottles() -> Vec<BottleSummary> {
+    let index = match fetch_bottle_index() {
+        Ok(index) => index,
+        Err(e) => {
+            ui::eprint_warning(&format!("Could not fetch bottle index: {}", e));
+            return Vec::new();
+        }
     };
 
-    let mut bottles = Vec::new();
-    for name in bottle_names {
-        let description = match fetch_bottle_manifest(&name) {
-            Ok(manifest) => manifest.description,
-            Err(_) => String::from("(unable to fetch description)"),
-        };
-        bottles.push((name, description));
-    }
-    bottles
+    index
+        .bottles
+        .into_iter()
+        .map(|entry| BottleSummary {
+            name: entry.name,
+            description: entry.description,
+            version: Some(entry.version),
+            channel: entry.channel,
+            deprecated: entry.deprecated,
+        })
+        .collect()
 }
 
 /// Find local bespoke bottles in ~/.bottle/bottles/
-fn list_bespoke_bottles() -> Vec<(String, String)> {
+fn list_bespoke_bottles() -> Vec<BottleSummary> {
     let bottles_dir = match get_bespoke_bottles_dir() {
         Some(dir) => dir,
         None => return Vec::new(),
@@ -89,19 +136,25 @@ fn list_bespoke_bottles() -> Vec<(String, String)> {
             None => continue,
         };
 
-        let description = match fs::read_to_string(&manifest_path) {
+        let (description, version) = match fs::read_to_string(&manifest_path) {
             Ok(content) => match serde_json::from_str::<BottleManifest>(&content) {
-                Ok(manifest) => manifest.description,
-                Err(_) => String::from("(invalid manifest)"),
+                Ok(manifest) => (manifest.description, Some(manifest.version)),
+                Err(_) => (String::from("(invalid manifest)"), None),
             },
-            Err(_) => String::from("(unable to read manifest)"),
+            Err(_) => (String::from("(unable to read manifest)"), None),
         };
 
-        bottles.push((name, description));
+        bottles.push(BottleSummary {
+            name,
+            description,
+            version,
+            channel: None,
+            deprecated: None,
+        });
     }
 
     // Sort by name for consistent output
-    bottles.sort_by(|a, b| a.0.cmp(&b.0));
+    bottles.sort_by(|a, b| a.name.cmp(&b.name));
     bottles
 }
 
@@ -0,0 +1,165 @@
+use crate::error::{BottleError, Result};
+use crate::install::log;
+use crate::ui::{self, OutputFormat};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use console::style;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One command's logs (a directory under ~/.bottle/logs)
+#[derive(Debug, Serialize)]
+struct RunInfo {
+    operation: String,
+    started_at: Option<DateTime<Utc>>,
+    path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct LogFile {
+    tool: String,
+    lines: usize,
+    path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct RunReport {
+    run: RunInfo,
+    logs: Vec<LogFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolLog {
+    tool: String,
+    run: RunInfo,
+    contents: String,
+}
+
+/// Show installer logs: the tools logged by the last run, or one tool's latest log
+pub fn run(tool: Option<&str>, format: OutputFormat) -> Result<()> {
+    let runs = log::runs();
+
+    match tool {
+        None => show_last_run(runs.first(), format),
+        Some(tool) => {
+            // The most recent run that touched this tool
+            let found = runs.iter().find_map(|run| {
+                let path = log::log_path(run, tool);
+                fs::read_to_string(&path)
+                    .ok()
+                    .map(|contents| (run, contents))
+            });
+            let Some((run, contents)) = found else {
+                return Err(BottleError::Other(format!(
+                    "No logs for '{}' in ~/.bottle/logs",
+                    tool
+                )));
+            };
+            show_tool_log(tool, run_info(run), contents, format)
+        }
+    }
+}
+
+fn show_last_run(run: Option<&PathBuf>, format: OutputFormat) -> Result<()> {
+    let Some(run) = run else {
+        if format.is_json() {
+            return ui::print_json("logs", &serde_json::json!({ "run": null, "logs": [] }));
+        }
+        println!("{}", style("No installer logs yet.").dim());
+        println!();
+        println!(
+            "Logs are written by {}, {}, {} and other commands that install tools.",
+            style("bottle install").cyan(),
+            style("bottle update").cyan(),
+            style("bottle switch").cyan()
+        );
+        return Ok(());
+    };
+
+    let mut logs: Vec<LogFile> = fs::read_dir(run)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .map(|path| LogFile {
+            tool: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            lines: fs::read_to_string(&path)
+                .map(|c| c.lines().count())
+                .unwrap_or(0),
+            path,
+        })
+        .collect();
+    logs.sort_by(|a, b| a.tool.cmp(&b.tool));
+
+    let report = RunReport {
+        run: run_info(run),
+        logs,
+    };
+    if format.is_json() {
+        return ui::print_json("logs", &report);
+    }
+
+    print_run_header("Last run", &report.run);
+    for file in &report.logs {
+        println!(
+            "  {:<14} {}",
+            file.tool,
+            style(format!("{} lines", file.lines)).dim()
+        );
+    }
+    println!();
+    println!("View one with {}", style("bottle logs <tool>").cyan());
+
+    Ok(())
+}
+
+fn show_tool_log(tool: &str, run: RunInfo, contents: String, format: OutputFormat) -> Result<()> {
+    if format.is_json() {
+        return ui::print_json(
+            "logs",
+            &ToolLog {
+                tool: tool.to_string(),
+                run,
+                contents,
+            },
+        );
+    }
+
+    print_run_header(&format!("{} log from", tool), &run);
+    print!("{}", contents);
+    Ok(())
+}
+
+fn print_run_header(label: &str, run: &RunInfo) {
+    let started = run
+        .started_at
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    println!(
+        "{}: {} {}",
+        style(label).bold(),
+        run.operation,
+        style(started).dim()
+    );
+    println!("  {}", style(run.path.display()).dim());
+    println!();
+}
+
+/// Run directories are named `<%Y%m%dT%H%M%SZ>-<operation>`
+fn run_info(path: &Path) -> RunInfo {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (stamp, operation) = name.split_once('-').unwrap_or(("", &name));
+
+    RunInfo {
+        operation: operation.to_string(),
+        started_at: NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%SZ")
+            .ok()
+            .map(|t| t.and_utc()),
+        path: path.to_path_buf(),
+    }
+}
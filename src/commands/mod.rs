@@ -2,13 +2,21 @@ pub mod agents_md;
 mod common;
 pub mod create;
 pub mod diff;
+pub mod doctor;
 pub mod eject;
+pub mod history;
+pub mod import;
 pub mod install;
 pub mod integrate;
 pub mod list;
+pub mod logs;
+pub mod promote;
 pub mod release;
+pub mod rollback;
 pub mod status;
 pub mod switch;
+mod transaction;
+pub mod uninstall;
 pub mod update;
 pub mod upgrade;
 pub mod validate;
@@ -0,0 +1,333 @@
+use super::common::get_local_manifest_path;
+use super::release::{check_git_clean, prepend_changelog};
+use crate::error::{BottleError, Result};
+use crate::manifest::bottle::Promotion;
+use crate::manifest::channel::Channel;
+use crate::version;
+use chrono::{DateTime, Duration, FixedOffset, SubsecRound, Utc};
+use console::style;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Manifest sections whose entries are version pins
+const PIN_SECTIONS: [(&str, &str); 2] =
+    [("tools", "Tools"), ("opencode_plugins", "OpenCode plugins")];
+
+/// A pin moving to the higher channel
+struct Promoted {
+    section: &'static str,
+    name: String,
+    from: Option<String>,
+    to: String,
+    /// When the pin landed on the lower channel
+    since: Option<DateTime<FixedOffset>>,
+}
+
+/// A pin left on the lower channel, and why
+struct Held {
+    name: String,
+    version: String,
+    reason: String,
+}
+
+/// Copy soaked pins from one release channel to a higher one (curator command)
+pub fn run(from: &str, to: &str, force: bool) -> Result<()> {
+    // 1. Pins only move up: edge → beta → stable
+    let (source, target) = parse_channels(from, to)?;
+
+    // 2. Only pins that validate are copied
+    super::validate::run(from)?;
+
+    println!(
+        "Promoting {} → {}...",
+        style(source).cyan(),
+        style(target).cyan()
+    );
+    println!();
+
+    // 3. Soak times come from the committed history of the lower channel
+    print!("  Checking git status... ");
+    check_git_clean()?;
+    println!("{}", style("✓").green());
+
+    let source_path = get_local_manifest_path(from)?;
+    let target_path = get_local_manifest_path(to)?;
+    let source_manifest: Value = serde_json::from_str(&fs::read_to_string(&source_path)?)?;
+    let mut target_manifest: Value = serde_json::from_str(&fs::read_to_string(&target_path)?)?;
+    let history = manifest_history(&source_path)?;
+
+    // 4. Pick the pins that differ and have soaked
+    let now = Utc::now().trunc_subsecs(0);
+    let mut promoted = Vec::new();
+    let mut held = Vec::new();
+    for (section, _) in PIN_SECTIONS {
+        let Some(pins) = source_manifest.get(section).and_then(|s| s.as_object()) else {
+            continue;
+        };
+        for (name, version) in pins {
+            let Some(version) = version.as_str() else {
+                continue;
+            };
+            let current = target_manifest
+                .get(section)
+                .and_then(|s| s.get(name))
+                .and_then(|v| v.as_str());
+            if current == Some(version) {
+                continue;
+            }
+
+            let hold = |reason: String| Held {
+                name: name.clone(),
+                version: version.to_string(),
+                reason,
+            };
+            if !version::is_exact(version) {
+                held.push(hold(format!("not an exact version on {}", source)));
+                continue;
+            }
+
+            let since = pinned_since(&history, section, name, version);
+            let days = since.map(|s| now.signed_duration_since(s).num_days());
+            let soaked = since.is_some_and(|s| {
+                now.signed_duration_since(s) >= Duration::days(target.soak_days())
+            });
+            if !soaked && !force {
+                held.push(hold(match days {
+                    Some(days) => format!(
+                        "on {} for {} day(s), {} needs {}",
+                        source,
+                        days,
+                        target,
+                        target.soak_days()
+                    ),
+                    None => format!("not in the committed {} manifest", source),
+                }));
+                continue;
+            }
+
+            promoted.push(Promoted {
+                section,
+                name: name.clone(),
+                from: current.map(|v| v.to_string()),
+                to: version.to_string(),
+                since,
+            });
+        }
+    }
+
+    if !held.is_empty() {
+        println!();
+        println!("  {}:", style("Held back").yellow());
+        for pin in &held {
+            println!(
+                "    {} {} {}",
+                pin.name,
+                pin.version,
+                style(format!("({})", pin.reason)).dim()
+            );
+        }
+    }
+
+    if promoted.is_empty() {
+        println!();
+        println!("{} Nothing to promote to {}.", style("✓").green(), target);
+        println!();
+        return Ok(());
+    }
+
+    // 5. Copy the pins forward, with where they came from
+    apply_pins(&mut target_manifest, &source_manifest, &promoted);
+    let promotion = Promotion {
+        channel: source.to_string(),
+        version: source_manifest
+            .get("version")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        commit: head_commit(),
+        promoted_at: now,
+    };
+    target_manifest["promoted_from"] = serde_json::to_value(&promotion)?;
+    let updated = serde_json::to_string_pretty(&target_manifest)?;
+    fs::write(&target_path, updated + "\n")?;
+
+    // 6. Record it in the target's changelog
+    let changelog_path = target_path.with_file_name("CHANGELOG.md");
+    prepend_changelog(
+        &changelog_path,
+        target.name(),
+        &changelog_entry(source, &promotion, &promoted),
+    )?;
+
+    // 7. Report
+    println!();
+    println!(
+        "{} Promoted {} pin(s) to {}:",
+        style("✓").green(),
+        promoted.len(),
+        style(target).cyan()
+    );
+    for pin in &promoted {
+        println!(
+            "  {:<14} {} → {}",
+            pin.name,
+            style(pin.from.as_deref().unwrap_or("(new)")).dim(),
+            style(&pin.to).green()
+        );
+    }
+    println!("  Updated {}", style(changelog_path.display()).dim());
+    println!();
+    println!("{}:", style("Next steps").bold());
+    println!(
+        "  {} - Verify manifest is valid",
+        style(format!("bottle validate {}", target)).cyan()
+    );
+    println!(
+        "  {} - Tag and publish",
+        style(format!("bottle release {}", target)).cyan()
+    );
+    println!();
+
+    Ok(())
+}
+
+fn parse_channels(from: &str, to: &str) -> Result<(Channel, Channel)> {
+    let parse = |name: &str| {
+        Channel::parse(name).ok_or_else(|| {
+            BottleError::Other(format!(
+                "'{}' is not a release channel (expected edge, beta or stable)",
+                name
+            ))
+        })
+    };
+    let (source, target) = (parse(from)?, parse(to)?);
+    if source >= target {
+        return Err(BottleError::Other(format!(
+            "Pins are promoted up the channels (edge → beta → stable), not from {} to {}",
+            source, target
+        )));
+    }
+    Ok((source, target))
+}
+
+/// Every committed version of a manifest with its commit time, newest first
+fn manifest_history(path: &Path) -> Result<Vec<(DateTime<FixedOffset>, Value)>> {
+    let output = Command::new("git")
+        .args(["log", "--format=%H %cI", "--"])
+        .arg(path)
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BottleError::Other(format!(
+            "Failed to read the history of {}.\n{}",
+            path.display(),
+            stderr.trim()
+        )));
+    }
+
+    let mut history = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((commit, date)) = line.split_once(' ') else {
+            continue;
+        };
+        let Ok(date) = DateTime::parse_from_rfc3339(date) else {
+            continue;
+        };
+        // `./` makes the path relative to the current directory, not the repo root
+        let shown = Command::new("git")
+            .arg("show")
+            .arg(format!("{}:./{}", commit, path.display()))
+            .output()?;
+        let Ok(manifest) = serde_json::from_slice::<Value>(&shown.stdout) else {
+            // Older than the manifest (or broken at that commit): nothing further back counts
+            break;
+        };
+        history.push((date, manifest));
+    }
+    Ok(history)
+}
+
+/// When a pin landed in the manifest: the oldest commit of the newest unbroken run
+/// of commits that have it
+fn pinned_since(
+    history: &[(DateTime<FixedOffset>, Value)],
+    section: &str,
+    name: &str,
+    version: &str,
+) -> Option<DateTime<FixedOffset>> {
+    history
+        .iter()
+        .take_while(|(_, manifest)| {
+            manifest
+                .get(section)
+                .and_then(|s| s.get(name))
+                .and_then(|v| v.as_str())
+                == Some(version)
+        })
+        .last()
+        .map(|(date, _)| *date)
+}
+
+/// Write promoted pins into the target manifest. A tool new to the channel brings
+/// its plugin along when the lower channel has one.
+fn apply_pins(target: &mut Value, source: &Value, promoted: &[Promoted]) {
+    for pin in promoted {
+        if !target[pin.section].is_object() {
+            target[pin.section] = Value::Object(Default::default());
+        }
+        target[pin.section][&pin.name] = Value::String(pin.to.clone());
+
+        let source_has_plugin = source
+            .get("plugins")
+            .and_then(|p| p.as_array())
+            .is_some_and(|p| p.iter().any(|p| p.as_str() == Some(&pin.name)));
+        if pin.section == "tools" && pin.from.is_none() && source_has_plugin {
+            if let Some(plugins) = target.get_mut("plugins").and_then(|p| p.as_array_mut()) {
+                if !plugins.iter().any(|p| p.as_str() == Some(&pin.name)) {
+                    plugins.push(Value::String(pin.name.clone()));
+                }
+            }
+        }
+    }
+}
+
+fn head_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn changelog_entry(source: Channel, promotion: &Promotion, promoted: &[Promoted]) -> String {
+    let mut entry = format!(
+        "## Promoted from {} {} ({})\n",
+        source,
+        promotion.version,
+        promotion.promoted_at.format("%Y-%m-%d")
+    );
+
+    for (section, heading) in PIN_SECTIONS {
+        let pins: Vec<&Promoted> = promoted.iter().filter(|p| p.section == section).collect();
+        if pins.is_empty() {
+            continue;
+        }
+        entry.push_str(&format!("\n### {}\n", heading));
+        for pin in pins {
+            let change = match &pin.from {
+                Some(from) => format!("{} → {}", from, pin.to),
+                None => format!("{} (new)", pin.to),
+            };
+            let since = pin
+                .since
+                .map(|s| format!(", on {} since {}", source, s.format("%Y-%m-%d")))
+                .unwrap_or_default();
+            entry.push_str(&format!("- {} {}{}\n", pin.name, change, since));
+        }
+    }
+
+    entry
+}
@@ -1,5 +1,10 @@
 use super::common::get_local_manifest_path;
+use super::diff::{diff_manifests, Change};
 use crate::error::{BottleError, Result};
+use crate::manifest::bottle::BottleManifest;
+use crate::manifest::index::BottleIndex;
+use crate::signature;
+use crate::ui;
 use chrono::Local;
 use console::style;
 use serde_json::Value;
@@ -7,10 +12,23 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// Tag and publish a bottle update (curator command)
-pub fn run(bottle: &str, message: Option<&str>) -> Result<()> {
+/// Tag and publish a bottle update (curator command).
+/// `dry_run` shows what would happen without writing anything; `push` is false for
+/// --no-push, which leaves the commit and tag local.
+pub fn run(
+    bottle: &str,
+    message: Option<&str>,
+    sign: bool,
+    signing_key: Option<&Path>,
+    dry_run: bool,
+    push: bool,
+) -> Result<()> {
     println!();
-    println!("Releasing {} bottle...", style(bottle).cyan());
+    if dry_run {
+        println!("Previewing {} release...", style(bottle).cyan());
+    } else {
+        println!("Releasing {} bottle...", style(bottle).cyan());
+    }
     println!();
 
     // 1. Run validation
@@ -18,33 +36,100 @@ pub fn run(bottle: &str, message: Option<&str>) -> Result<()> {
     validate_manifest(bottle)?;
     println!("{}", style("✓").green());
 
-    // 2. Check git status is clean
+    // 2. Check git status is clean (a dry run previews uncommitted edits too)
     print!("  Checking git status... ");
-    check_git_clean()?;
-    println!("{}", style("✓").green());
+    match check_git_clean() {
+        Ok(()) => println!("{}", style("✓").green()),
+        Err(_) if dry_run => println!(
+            "{}",
+            style("uncommitted changes (commit them before releasing)").yellow()
+        ),
+        Err(e) => return Err(e),
+    }
 
-    // 3. Bump manifest version to today's date
+    // 3. Work out the release: next free version, changelog, commit and tag. Tags
+    // other curators pushed count too, unless this release stays local.
     let manifest_path = get_local_manifest_path(bottle)?;
-    let (old_version, new_version) = bump_version(&manifest_path)?;
+    let old_version = current_version(&manifest_path)?;
+    let (remote, tags_checked) = if push {
+        match remote_tags(bottle) {
+            Ok(tags) => (tags, "local and origin tags"),
+            Err(_) if dry_run => (Vec::new(), "local tags only (origin unreachable)"),
+            Err(e) => return Err(e),
+        }
+    } else {
+        (Vec::new(), "local tags only (--no-push)")
+    };
+    let new_version = next_version(bottle, &remote)?;
+    let previous = previous_tag(bottle)?;
+    let entry = changelog_entry(&manifest_path, previous.as_deref(), &new_version, message)?;
+    let changelog_path = manifest_path.with_file_name("CHANGELOG.md");
+    let commit_msg = format_commit_message(bottle, &new_version, message);
+    let tag_name = format!("{}-{}", bottle, new_version);
+
+    if dry_run {
+        show_plan(&ReleasePlan {
+            old_version: &old_version,
+            new_version: &new_version,
+            changelog_path: &changelog_path,
+            previous: previous.as_deref(),
+            entry: &entry,
+            commit_msg: &commit_msg,
+            tag_name: &tag_name,
+            tags_checked,
+            push,
+        });
+        return Ok(());
+    }
+
+    // 4. Bump manifest version
+    set_version(&manifest_path, &new_version)?;
     println!(
         "  Bumped version: {} → {}",
         style(&old_version).dim(),
         style(&new_version).green()
     );
 
-    // 4. Commit the version bump
-    let commit_msg = format_commit_message(bottle, &new_version, message);
-    git_commit(&manifest_path, &commit_msg)?;
+    // 5. Changelog entry from the manifest delta since the previous release
+    prepend_changelog(&changelog_path, bottle, &entry)?;
+    println!(
+        "  Updated {} (changes since {})",
+        style(changelog_path.display()).dim(),
+        previous.as_deref().unwrap_or("the first release")
+    );
+
+    // 6. Keep the curated index in sync with the manifest
+    let mut changed = vec![manifest_path.clone(), changelog_path];
+    if let Some(index_path) = update_index(bottle, &new_version)? {
+        println!("  Updated {}", style(index_path.display()).dim());
+        changed.push(index_path);
+    }
+
+    // 7. Sign the manifest and its tool definitions (curators with a release key)
+    if sign {
+        let signatures = sign_release(&manifest_path, bottle, &new_version, signing_key)?;
+        println!("  Signed {} file(s)", signatures.len());
+        changed.extend(signatures);
+    }
+
+    // 8. Commit the version bump
+    git_commit(&changed, &commit_msg)?;
     println!("  Committed: {}", style(&commit_msg).dim());
 
-    // 5. Create git tag
-    let tag_name = format!("{}-{}", bottle, new_version);
-    git_tag(&tag_name, message)?;
+    // 9. Create git tag, annotated with the changelog entry
+    git_tag(&tag_name, &tag_message(bottle, &new_version, &entry))?;
     println!("  Tagged: {}", style(&tag_name).cyan());
 
-    // 6. Push commit + tag
-    git_push(&tag_name)?;
-    println!("  Pushed to origin {}", style("✓").green());
+    // 10. Push commit + tag
+    if push {
+        git_push(&tag_name)?;
+        println!("  Pushed to origin {}", style("✓").green());
+    } else {
+        println!(
+            "  Not pushed (--no-push). To publish: {}",
+            style(format!("git push origin && git push origin {}", tag_name)).cyan()
+        );
+    }
 
     println!();
     println!(
@@ -58,6 +143,64 @@ pub fn run(bottle: &str, message: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// Everything a release would do, for --dry-run
+struct ReleasePlan<'a> {
+    old_version: &'a str,
+    new_version: &'a str,
+    changelog_path: &'a Path,
+    previous: Option<&'a str>,
+    entry: &'a str,
+    commit_msg: &'a str,
+    tag_name: &'a str,
+    /// Which tags `next_version` looked at
+    tags_checked: &'a str,
+    push: bool,
+}
+
+fn show_plan(plan: &ReleasePlan) {
+    println!(
+        "  Version:   {} → {}",
+        style(plan.old_version).dim(),
+        style(plan.new_version).green()
+    );
+    println!(
+        "  Changelog: {} (changes since {})",
+        plan.changelog_path.display(),
+        plan.previous.unwrap_or("the first release")
+    );
+    println!("  Commit:    {}", plan.commit_msg);
+    println!("  Tag:       {}", style(plan.tag_name).cyan());
+    println!("  Checked:   {}", style(plan.tags_checked).dim());
+    if plan.push {
+        println!("  Push:      {}", push_target());
+    } else {
+        println!("  Push:      {}", style("none (--no-push)").dim());
+    }
+
+    println!();
+    for line in plan.entry.lines() {
+        println!("    {}", style(line).dim());
+    }
+    println!();
+    println!("Dry run: nothing was written, committed, tagged or pushed.");
+    println!();
+}
+
+/// Where `git_push` sends the release: origin's URL and the current branch
+fn push_target() -> String {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let url = git(&["remote", "get-url", "origin"]).unwrap_or_else(|| "no URL".to_string());
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).unwrap_or_else(|| "HEAD".to_string());
+    format!("origin ({}), branch {}", url, branch)
+}
+
 /// Validate the manifest (mirrors validate command logic)
 fn validate_manifest(bottle: &str) -> Result<()> {
     let manifest_path = get_local_manifest_path(bottle)?;
@@ -101,7 +244,7 @@ fn validate_manifest(bottle: &str) -> Result<()> {
 }
 
 /// Check that git working directory is clean
-fn check_git_clean() -> Result<()> {
+pub(super) fn check_git_clean() -> Result<()> {
     let output = Command::new("git")
         .args(["status", "--porcelain"])
         .output()?;
@@ -125,32 +268,297 @@ fn check_git_clean() -> Result<()> {
         if files.len() > 10 {
             msg.push_str(&format!("  ... and {} more\n", files.len() - 10));
         }
-        msg.push_str("\nCommit your changes before releasing.");
+        msg.push_str("\nCommit your changes first.");
         return Err(BottleError::Other(msg));
     }
 
     Ok(())
 }
 
-/// Bump the version field to today's date and return (old, new) versions
-fn bump_version(manifest_path: &PathBuf) -> Result<(String, String)> {
-    let contents = fs::read_to_string(manifest_path)?;
-    let mut manifest: Value = serde_json::from_str(&contents)?;
-
-    let old_version = manifest
+/// The manifest's current version
+fn current_version(manifest_path: &Path) -> Result<String> {
+    let manifest: Value = serde_json::from_str(&fs::read_to_string(manifest_path)?)?;
+    Ok(manifest
         .get("version")
         .and_then(|v| v.as_str())
         .unwrap_or("unknown")
-        .to_string();
+        .to_string())
+}
 
-    let new_version = Local::now().format("%Y.%m.%d").to_string();
+/// Today's date as a version, or `YYYY.MM.DD.N` (N from 2) when today's release tag
+/// already exists locally or in `remote` (tags on origin)
+fn next_version(bottle: &str, remote: &[String]) -> Result<String> {
+    let today = Local::now().format("%Y.%m.%d").to_string();
+    let taken = |version: &str| -> Result<bool> {
+        let tag_name = format!("{}-{}", bottle, version);
+        Ok(remote.contains(&tag_name) || tag_exists(&tag_name)?)
+    };
+    if !taken(&today)? {
+        return Ok(today);
+    }
+    for n in 2.. {
+        let version = format!("{}.{}", today, n);
+        if !taken(&version)? {
+            return Ok(version);
+        }
+    }
+    unreachable!("some release number is free")
+}
 
-    manifest["version"] = Value::String(new_version.clone());
+/// Set the manifest's version field
+fn set_version(manifest_path: &Path, version: &str) -> Result<()> {
+    let contents = fs::read_to_string(manifest_path)?;
+    let mut manifest: Value = serde_json::from_str(&contents)?;
+    manifest["version"] = Value::String(version.to_string());
 
     let updated = serde_json::to_string_pretty(&manifest)?;
     fs::write(manifest_path, updated + "\n")?;
+    Ok(())
+}
+
+/// Set the bottle's version in index.json, returning the path if it changed
+fn update_index(bottle: &str, version: &str) -> Result<Option<PathBuf>> {
+    let index_path = PathBuf::from(BottleIndex::PATH);
+    if !index_path.exists() {
+        ui::print_warning(&format!(
+            "No {} found; curated bottles will not be listed",
+            BottleIndex::PATH
+        ));
+        return Ok(None);
+    }
+
+    // Edit as a Value so fields this binary doesn't know about are preserved
+    let contents = fs::read_to_string(&index_path)?;
+    let mut index: Value = serde_json::from_str(&contents)?;
+
+    let entry = index
+        .get_mut("bottles")
+        .and_then(|b| b.as_array_mut())
+        .and_then(|bottles| {
+            bottles
+                .iter_mut()
+                .find(|b| b.get("name").and_then(|n| n.as_str()) == Some(bottle))
+        });
+
+    let Some(entry) = entry else {
+        ui::print_warning(&format!(
+            "'{}' is not listed in {}; add it so `bottle list` shows it",
+            bottle,
+            BottleIndex::PATH
+        ));
+        return Ok(None);
+    };
+
+    entry["version"] = Value::String(version.to_string());
+
+    let updated = serde_json::to_string_pretty(&index)?;
+    fs::write(&index_path, updated + "\n")?;
+
+    Ok(Some(index_path))
+}
+
+/// Sign the manifest and every tool definition it references with minisign.
+/// Returns the paths of the written `.minisig` files.
+fn sign_release(
+    manifest_path: &Path,
+    bottle: &str,
+    version: &str,
+    signing_key: Option<&Path>,
+) -> Result<Vec<PathBuf>> {
+    if which::which("minisign").is_err() {
+        return Err(BottleError::Other(
+            "minisign not found. Install it (brew install minisign) to sign releases.".to_string(),
+        ));
+    }
+
+    let contents = fs::read_to_string(manifest_path)?;
+    let manifest: Value = serde_json::from_str(&contents)?;
+
+    let mut files = vec![manifest_path.to_path_buf()];
+    if let Some(tools) = manifest.get("tools").and_then(|t| t.as_object()) {
+        let mut names: Vec<&String> = tools.keys().collect();
+        names.sort();
+        files.extend(
+            names
+                .iter()
+                .map(|t| PathBuf::from(format!("tools/{}.json", t))),
+        );
+    }
+
+    // One invocation so minisign asks for the key password once
+    let trusted_comment = format!("bottle {} {}", bottle, version);
+    let mut command = Command::new("minisign");
+    command.args(["-S", "-t", &trusted_comment]);
+    if let Some(key) = signing_key {
+        command.arg("-s").arg(key);
+    }
+    command.arg("-m").args(&files);
+
+    if !command.status()?.success() {
+        return Err(BottleError::Other(
+            "minisign failed to sign the release".to_string(),
+        ));
+    }
+
+    Ok(files
+        .iter()
+        .map(|f| PathBuf::from(signature::signature_path(&f.to_string_lossy())))
+        .collect())
+}
+
+/// The newest `<bottle>-<version>` tag on the current branch (date versions are
+/// zero-padded, so version order is release order, same-day suffixes included)
+fn previous_tag(bottle: &str) -> Result<Option<String>> {
+    let prefix = format!("{}-", bottle);
+    let output = Command::new("git")
+        .args(["tag", "--list", "--merged", "HEAD", "--sort=-v:refname"])
+        .arg(format!("{}*", prefix))
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BottleError::Other(format!(
+            "Failed to list release tags.\n{}",
+            stderr.trim()
+        )));
+    }
+
+    // `stable-*` also matches another bottle's tags, e.g. `stable-team-2026.01.20`
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|tag| {
+            tag.strip_prefix(&prefix)
+                .is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .map(|tag| tag.to_string()))
+}
+
+/// Changelog entry for a release: what changed in the manifest since `previous`
+/// (everything, for a first release)
+fn changelog_entry(
+    manifest_path: &Path,
+    previous: Option<&str>,
+    version: &str,
+    message: Option<&str>,
+) -> Result<String> {
+    let current: BottleManifest = serde_json::from_str(&fs::read_to_string(manifest_path)?)?;
+    let before = match previous {
+        Some(tag) => manifest_at(tag, manifest_path)?,
+        None => BottleManifest {
+            tools: Default::default(),
+            plugins: Vec::new(),
+            opencode_plugins: Default::default(),
+            mcp_servers: Default::default(),
+            ..current.clone()
+        },
+    };
+
+    let mut entry = format!("## {}\n", version);
+    if let Some(message) = message {
+        entry.push_str(&format!("\n{}\n", message));
+    }
+    if previous.is_none() {
+        entry.push_str("\nInitial release.\n");
+    }
+
+    let diff = diff_manifests(&before, &current, Vec::new());
+    let mut changed = false;
+    for section in &diff.sections {
+        if !CHANGELOG_SECTIONS.contains(&section.section) {
+            continue;
+        }
+        let lines: Vec<String> = section
+            .entries
+            .iter()
+            .filter_map(|e| changelog_line(&e.name, &e.change))
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        changed = true;
+        entry.push_str(&format!("\n### {}\n", section.title));
+        for line in lines {
+            entry.push_str(&format!("- {}\n", line));
+        }
+    }
+    if !changed && previous.is_some() {
+        entry.push_str("\nNo manifest changes.\n");
+    }
+
+    Ok(entry)
+}
+
+/// Manifest sections a release changelog covers
+const CHANGELOG_SECTIONS: [&str; 4] = ["tools", "plugins", "opencode_plugins", "mcp_servers"];
+
+fn changelog_line(name: &str, change: &Change) -> Option<String> {
+    let with = |detail: &str, note: &str| {
+        if detail.is_empty() {
+            format!("{} ({})", name, note)
+        } else {
+            format!("{} {} ({})", name, detail, note)
+        }
+    };
+    Some(match change {
+        Change::Unchanged { .. } => return None,
+        Change::Added { to } => with(to, "added"),
+        Change::Removed { from } => with(from, "removed"),
+        Change::Upgraded { from, to } | Change::Changed { from, to } => {
+            format!("{} {} → {}", name, from, to)
+        }
+        Change::Downgraded { from, to } => with(&format!("{} → {}", from, to), "downgrade"),
+    })
+}
+
+/// A manifest as it was at a tag
+fn manifest_at(tag: &str, manifest_path: &Path) -> Result<BottleManifest> {
+    // `./` makes the path relative to the current directory, not the repo root
+    let output = Command::new("git")
+        .arg("show")
+        .arg(format!("{}:./{}", tag, manifest_path.display()))
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BottleError::Other(format!(
+            "Failed to read {} at tag '{}'.\n{}",
+            manifest_path.display(),
+            tag,
+            stderr.trim()
+        )));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// Add an entry at the top of a bottle's changelog, under its title
+pub(super) fn prepend_changelog(path: &Path, bottle: &str, entry: &str) -> Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let title = match bottle.chars().next() {
+        Some(first) => format!(
+            "# {}{} Bottle Changelog",
+            first.to_uppercase(),
+            &bottle[first.len_utf8()..]
+        ),
+        None => "# Changelog".to_string(),
+    };
+    let (title, rest) = match existing.split_once('\n') {
+        Some((first, rest)) if first.starts_with("# ") => (first.to_string(), rest),
+        _ => (title, existing.as_str()),
+    };
+    let contents = format!("{}\n\n{}\n{}", title, entry, rest.trim_start_matches('\n'));
+    fs::write(path, contents.trim_end().to_string() + "\n")?;
+    Ok(())
+}
 
-    Ok((old_version, new_version))
+/// Annotated tag message: a summary line, then the changelog entry without its heading
+fn tag_message(bottle: &str, version: &str, entry: &str) -> String {
+    let body = entry.split_once('\n').map(|(_, body)| body).unwrap_or("");
+    format!("Release {} {}\n{}", bottle, version, body)
+        .trim_end()
+        .to_string()
 }
 
 /// Format the commit message
@@ -161,14 +569,10 @@ fn format_commit_message(bottle: &str, version: &str, message: Option<&str>) ->
     }
 }
 
-/// Commit the manifest change
-fn git_commit(manifest_path: &Path, message: &str) -> Result<()> {
-    let path_str = manifest_path
-        .to_str()
-        .ok_or_else(|| BottleError::Other("Invalid path encoding".to_string()))?;
-
-    // Stage the manifest
-    let status = Command::new("git").args(["add", path_str]).status()?;
+/// Commit the manifest (and index) change
+fn git_commit(paths: &[PathBuf], message: &str) -> Result<()> {
+    // Stage the changed files
+    let status = Command::new("git").arg("add").args(paths).status()?;
 
     if !status.success() {
         return Err(BottleError::Other("Failed to stage manifest".to_string()));
@@ -186,9 +590,8 @@ fn git_commit(manifest_path: &Path, message: &str) -> Result<()> {
     Ok(())
 }
 
-/// Create a git tag
-fn git_tag(tag_name: &str, message: Option<&str>) -> Result<()> {
-    // Check if tag already exists
+/// Whether a tag exists locally
+fn tag_exists(tag_name: &str) -> Result<bool> {
     let output = Command::new("git").args(["tag", "-l", tag_name]).output()?;
 
     // Verify the command succeeded before trusting output
@@ -200,20 +603,49 @@ fn git_tag(tag_name: &str, message: Option<&str>) -> Result<()> {
         )));
     }
 
-    let existing = String::from_utf8_lossy(&output.stdout);
-    if !existing.trim().is_empty() {
+    Ok(!String::from_utf8_lossy(&output.stdout).trim().is_empty())
+}
+
+/// The bottle's release tags on origin
+fn remote_tags(bottle: &str) -> Result<Vec<String>> {
+    let pattern = format!("refs/tags/{}-*", bottle);
+    let output = Command::new("git")
+        .args(["ls-remote", "--tags", "origin", &pattern])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BottleError::Other(format!(
+            "Failed to list tags on origin.\n{}\n\n\
+            Use --no-push to release locally without checking origin.",
+            stderr.trim()
+        )));
+    }
+
+    // Lines are "<sha>\trefs/tags/<name>", plus "<name>^{}" for annotated tags
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1)?.strip_prefix("refs/tags/"))
+        .filter(|name| !name.ends_with("^{}"))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Create an annotated git tag
+fn git_tag(tag_name: &str, message: &str) -> Result<()> {
+    // next_version picked a free name, but another release may have raced us
+    if tag_exists(tag_name)? {
         return Err(BottleError::Other(format!(
-            "Tag '{}' already exists. A release was already made today.\n\
-            Either wait until tomorrow or delete the existing tag with:\n  \
+            "Tag '{}' already exists. Delete it with:\n  \
             git tag -d {} && git push origin :refs/tags/{}",
             tag_name, tag_name, tag_name
         )));
     }
 
-    // Create annotated tag
-    let tag_msg = message.unwrap_or("Release");
+    // Create annotated tag ("whitespace" keeps the changelog's `###` headings, which
+    // the default cleanup strips as comments)
     let status = Command::new("git")
-        .args(["tag", "-a", tag_name, "-m", tag_msg])
+        .args(["tag", "-a", "--cleanup=whitespace", tag_name, "-m", message])
         .status()?;
 
     if !status.success() {
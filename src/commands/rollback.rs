@@ -0,0 +1,132 @@
+use super::common::{recorded_source, require_dry_run_for_json};
+use super::switch::apply_switch;
+use crate::error::{BottleError, Result};
+use crate::manifest::bottle::BottleManifest;
+use crate::manifest::history::{HistoryEntry, Operation};
+use crate::manifest::state::BottleState;
+use crate::ui::{self, OutputFormat};
+use console::style;
+use std::collections::HashMap;
+
+/// Roll back to an earlier snapshot from the history
+///
+/// `target` is either an index from `bottle history` (default 1, the previous
+/// snapshot) or a bottle version to return to.
+pub fn run(
+    target: Option<&str>,
+    yes: bool,
+    dry_run: bool,
+    strict: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    require_dry_run_for_json(format, dry_run)?;
+
+    // 1. Load current state
+    let state = BottleState::load().ok_or(BottleError::NoBottleInstalled)?;
+
+    if !state.is_managed() {
+        return Err(BottleError::AlreadyEjected);
+    }
+
+    // 2. Find the snapshot to return to (newest first, index 0 is current)
+    let history: Vec<HistoryEntry> = HistoryEntry::load_all().into_iter().rev().collect();
+    let (index, entry) = select_snapshot(&history, target)?;
+
+    if !format.is_json() {
+        println!(
+            "Rolling back to snapshot #{} ({} {} from {})",
+            index,
+            style(&entry.bottle).cyan(),
+            entry.bottle_version,
+            entry.timestamp.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    // 3. Replay the snapshot through the switch planner
+    let manifest = snapshot_manifest(entry);
+    if !apply_switch(
+        &state,
+        &manifest,
+        recorded_source(&manifest.name),
+        None,
+        Operation::Rollback,
+        yes,
+        dry_run,
+        strict,
+        format,
+    )? {
+        return Ok(());
+    }
+
+    // 4. Show success
+    println!();
+    ui::print_success(&format!(
+        "Rolled back to {} {}",
+        entry.bottle, entry.bottle_version
+    ));
+
+    Ok(())
+}
+
+/// Pick a snapshot by index or by bottle version
+fn select_snapshot<'a>(
+    history: &'a [HistoryEntry],
+    target: Option<&str>,
+) -> Result<(usize, &'a HistoryEntry)> {
+    if history.is_empty() {
+        return Err(BottleError::Other(
+            "No history recorded yet. History starts with the next install, update or switch."
+                .to_string(),
+        ));
+    }
+
+    let target = target.unwrap_or("1");
+
+    if let Ok(index) = target.parse::<usize>() {
+        return history.get(index).map(|e| (index, e)).ok_or_else(|| {
+            BottleError::Other(format!(
+                "No snapshot #{} (history has {} entries, see `bottle history`)",
+                index,
+                history.len()
+            ))
+        });
+    }
+
+    // Skip the current snapshot so `rollback <version>` always moves somewhere
+    history
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, e)| e.bottle_version == target)
+        .ok_or_else(|| {
+            BottleError::Other(format!(
+                "No earlier snapshot with version '{}' (see `bottle history`)",
+                target
+            ))
+        })
+}
+
+/// Build a manifest that pins exactly the tools recorded in a snapshot
+/// AIDEV-NOTE: Snapshots only record tool versions, so plugins, MCP servers and the
+/// AGENTS.md snippet are left as they are. Custom tools are carried over by the
+/// switch planner like any other switch.
+fn snapshot_manifest(entry: &HistoryEntry) -> BottleManifest {
+    BottleManifest {
+        name: entry.bottle.clone(),
+        version: entry.bottle_version.clone(),
+        description: format!("Snapshot from {}", entry.timestamp.format("%Y-%m-%d %H:%M")),
+        tools: entry
+            .tools
+            .iter()
+            .map(|(name, version)| (name.clone(), version.clone()))
+            .collect::<HashMap<_, _>>(),
+        plugins: Vec::new(),
+        prerequisites: HashMap::new(),
+        opencode_plugins: HashMap::new(),
+        mcp_servers: HashMap::new(),
+        agents_md: None,
+        custom_tools: HashMap::new(),
+        brew_policy: Default::default(),
+        promoted_from: None,
+    }
+}
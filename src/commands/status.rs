@@ -1,39 +1,241 @@
+use super::common::{fetch_recorded_source, load_project, resolve_versions};
+use super::update::{calculate_changes, PlannedChange, ToolChange};
 use crate::error::{BottleError, Result};
-use crate::fetch::fetch_bottle_manifest;
+use crate::fetch::{fetch_bottle_index, fetch_bottle_manifest};
+use crate::manifest::bottle::BottleManifest;
+use crate::manifest::project::{Lockfile, Project};
 use crate::manifest::state::{BottleState, Mode};
-use crate::ui;
+use crate::ui::{self, OutputFormat};
+use chrono::{DateTime, Utc};
 use console::style;
+use serde::Serialize;
+use std::path::PathBuf;
 use std::process::Command;
 
+/// Status of the active bottle, shared by the human and JSON renderers
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    /// Present inside a project with a bottle.json; `state` is then the project's bottle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<ProjectStatus>,
+    /// None when no bottle is installed
+    state: Option<BottleStatus>,
+    /// Present only with --check-updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    update: Option<UpdateCheck>,
+}
+
+#[derive(Debug, Serialize)]
+struct BottleStatus {
+    bottle: String,
+    version: String,
+    mode: Mode,
+    installed_at: DateTime<Utc>,
+    /// Where a shared bottle was installed from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    tools: Vec<ToolStatus>,
+    integrations: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolStatus {
+    name: String,
+    version: String,
+    installed: bool,
+    /// Version the binary reported when it was installed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    observed_version: Option<String>,
+    version_mismatch: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ProjectStatus {
+    /// Path of the project's bottle.json
+    manifest: PathBuf,
+    bottle: String,
+    /// Whether the project's bottle is the active one
+    active: bool,
+    lock: LockStatus,
+}
+
+/// How bottle.lock compares with what is installed
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum LockStatus {
+    /// Matches the installed versions
+    Current,
+    /// Records different versions than are installed
+    Stale,
+    /// No bottle.lock yet
+    Missing,
+    /// bottle.lock could not be read
+    Invalid,
+}
+
+/// Result of comparing state against the latest published manifest
+#[derive(Debug, Serialize)]
+struct UpdateCheck {
+    /// Latest manifest version, if it could be fetched
+    latest_version: Option<String>,
+    up_to_date: bool,
+    changes: Vec<PlannedChange>,
+    /// Why the check could not be completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 /// Show current bottle status and installed tools
-pub fn run(check_updates: bool) -> Result<()> {
-    let state = match BottleState::load() {
-        Some(s) => s,
-        None => {
-            println!("{}", style("No bottle installed.").dim());
-            println!();
-            println!("Install a bottle with:");
-            println!(
-                "  {} {}",
-                style("bottle install").cyan(),
-                style("stable").dim()
-            );
+pub fn run(check_updates: bool, format: OutputFormat) -> Result<()> {
+    let report = build_report(check_updates)?;
+
+    if format.is_json() {
+        return ui::print_json("status", &report);
+    }
+
+    show_report(&report);
+    Ok(())
+}
+
+/// Gather state, installed-tool checks, and (optionally) update information
+fn build_report(check_updates: bool) -> Result<StatusReport> {
+    let project = load_project()?;
+    let state = match &project {
+        Some((_, manifest)) => BottleState::load_for(&manifest.name),
+        None => BottleState::load(),
+    };
+    let project_status = project
+        .as_ref()
+        .map(|(project, manifest)| project_status(project, manifest, state.as_ref()));
+
+    let Some(state) = state else {
+        return Ok(StatusReport {
+            project: project_status,
+            state: None,
+            update: None,
+        });
+    };
+
+    // Sort tools for consistent output
+    let mut tools: Vec<ToolStatus> = state
+        .tools
+        .iter()
+        .map(|(name, tool_state)| ToolStatus {
+            name: name.clone(),
+            version: tool_state.version.clone(),
+            installed: check_tool_installed(name),
+            observed_version: tool_state.reported_version().map(str::to_string),
+            version_mismatch: tool_state.version_mismatch(),
+        })
+        .collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut integrations: Vec<String> = state.integrations.keys().cloned().collect();
+    integrations.sort();
+
+    let update = if check_updates {
+        Some(check_for_updates(
+            &state,
+            project.as_ref().map(|(_, manifest)| manifest),
+        )?)
+    } else {
+        None
+    };
+
+    Ok(StatusReport {
+        project: project_status,
+        state: Some(BottleStatus {
+            bottle: state.bottle.clone(),
+            version: state.bottle_version.clone(),
+            mode: state.mode.clone(),
+            installed_at: state.installed_at,
+            source: state.source.clone(),
+            tools,
+            integrations,
+        }),
+        update,
+    })
+}
+
+/// Where a project's bottle stands relative to what is installed
+fn project_status(
+    project: &Project,
+    manifest: &BottleManifest,
+    state: Option<&BottleState>,
+) -> ProjectStatus {
+    let lock = match (Lockfile::load(&project.lock_path()), state) {
+        (Ok(None), _) => LockStatus::Missing,
+        (Err(_), _) => LockStatus::Invalid,
+        (Ok(Some(lock)), Some(state)) if lock == Lockfile::from_state(state) => LockStatus::Current,
+        (Ok(Some(_)), _) => LockStatus::Stale,
+    };
+
+    ProjectStatus {
+        manifest: project.manifest_path(),
+        bottle: manifest.name.clone(),
+        active: BottleState::active_bottle().as_deref() == Some(manifest.name.as_str()),
+        lock,
+    }
+}
+
+/// Render the status report for humans
+fn show_report(report: &StatusReport) {
+    if let Some(project) = &report.project {
+        println!(
+            "{}: {}",
+            style("Project").bold(),
+            style(project.manifest.display()).dim()
+        );
+        if report.state.is_none() {
             println!();
-            println!("Available bottles:");
-            println!(
-                "  {}  Production-ready Open Horizon Labs stack",
-                style("stable").cyan()
-            );
             println!(
-                "  {}    Latest features, may be unstable",
-                style("edge").cyan()
+                "Project bottle {} is not installed. Install it with:",
+                style(&project.bottle).cyan()
             );
-            return Ok(());
+            println!("  {}", style("bottle install").cyan());
+            return;
+        }
+    }
+
+    let Some(state) = &report.state else {
+        println!("{}", style("No bottle installed.").dim());
+        println!();
+        println!("Install a bottle with:");
+        println!(
+            "  {} {}",
+            style("bottle install").cyan(),
+            style("stable").dim()
+        );
+
+        // Available bottles come from the registry index; skip quietly if unreachable
+        if let Ok(index) = fetch_bottle_index() {
+            let available: Vec<_> = index
+                .bottles
+                .iter()
+                .filter(|b| b.deprecated.is_none())
+                .collect();
+            if !available.is_empty() {
+                println!();
+                println!("Available bottles:");
+                for bottle in available {
+                    println!(
+                        "  {:<10} {}",
+                        style(&bottle.name).cyan(),
+                        bottle.description
+                    );
+                }
+            }
         }
+        return;
     };
 
     // Show bottle header
-    ui::print_bottle_header(&state.bottle, &state.bottle_version);
+    ui::print_bottle_header(&state.bottle, &state.version);
+
+    if let Some(source) = &state.source {
+        println!("{} {}", style("Source:").dim(), source);
+        println!();
+    }
 
     // Show mode if ejected
     if matches!(state.mode, Mode::Ejected) {
@@ -43,35 +245,69 @@ pub fn run(check_updates: bool) -> Result<()> {
 
     // Show tools
     println!("{}:", style("Tools").bold());
-
-    // Sort tools for consistent output
-    let mut tools: Vec<_> = state.tools.iter().collect();
-    tools.sort_by_key(|(name, _)| *name);
-
-    for (name, tool_state) in &tools {
-        let installed = check_tool_installed(name);
-        let status_icon = if installed {
-            style("installed").green()
+    for tool in &state.tools {
+        let status_icon = if !tool.installed {
+            style("missing".to_string()).red()
+        } else if tool.version_mismatch {
+            style(format!(
+                "installed (reports {})",
+                tool.observed_version.as_deref().unwrap_or("?")
+            ))
+            .yellow()
         } else {
-            style("missing").red()
+            style("installed".to_string()).green()
         };
-        println!("  {:<12} {:<8} {}", name, tool_state.version, status_icon);
+        println!("  {:<12} {:<8} {}", tool.name, tool.version, status_icon);
     }
     println!();
 
+    if state.tools.iter().any(|t| t.version_mismatch) {
+        ui::print_warning(&format!(
+            "Some tools report a different version than the bottle pins. Run {} to reinstall them.",
+            style("bottle doctor --fix").cyan()
+        ));
+        println!();
+    }
+
     // Show plugins count
     // AIDEV-NOTE: Plugin count comes from the manifest, not the state.
     // For now, we show the count of tools as a proxy (each tool has a plugin).
     // Future: Track plugins separately in BottleState if they diverge from tools.
-    println!("Plugins: {} configured", tools.len());
+    println!("Plugins: {} configured", state.tools.len());
     println!();
 
-    // Check for updates if requested
-    if check_updates {
-        check_for_updates(&state)?;
+    if let Some(project) = &report.project {
+        show_project_warnings(project);
     }
 
-    Ok(())
+    if let Some(update) = &report.update {
+        show_update_check(&state.bottle, update);
+    }
+}
+
+/// Point out a project bottle that is not active or whose lock is out of date
+fn show_project_warnings(project: &ProjectStatus) {
+    if !project.active {
+        ui::print_warning(&format!(
+            "Another bottle is active, so its tools are the ones on PATH. Run {} here to switch to {}.",
+            style("bottle install").cyan(),
+            project.bottle
+        ));
+        println!();
+    }
+
+    let lock = match project.lock {
+        LockStatus::Current => return,
+        LockStatus::Stale => "bottle.lock does not match the installed versions",
+        LockStatus::Missing => "No bottle.lock yet",
+        LockStatus::Invalid => "bottle.lock could not be read",
+    };
+    ui::print_warning(&format!(
+        "{}. Run {} to write it.",
+        lock,
+        style("bottle update").cyan()
+    ));
+    println!();
 }
 
 /// Check if a tool binary is actually installed and accessible
@@ -107,19 +343,59 @@ fn check_mcp_registered(name: &str) -> bool {
         .unwrap_or(false)
 }
 
-/// Check for available updates by comparing with latest manifest
-fn check_for_updates(state: &BottleState) -> Result<()> {
+/// Check for available updates by comparing with latest manifest (for a project
+/// bottle, its bottle.json)
+fn check_for_updates(state: &BottleState, project: Option<&BottleManifest>) -> Result<UpdateCheck> {
+    // bottle.json may be edited without bumping its version
+    if let Some(manifest) = project {
+        let changes = calculate_changes(state, &resolve_versions(manifest, None));
+        return Ok(UpdateCheck {
+            up_to_date: manifest.version == state.bottle_version && changes.is_empty(),
+            changes,
+            latest_version: Some(manifest.version.clone()),
+            error: None,
+        });
+    }
+
     let spinner = ui::spinner("Checking for updates...");
 
-    let latest = match fetch_bottle_manifest(&state.bottle) {
+    // A shared bottle is checked against where it was installed from
+    let latest = match &state.source {
+        Some(source) => fetch_recorded_source(source).map(|shared| shared.manifest),
+        None => fetch_bottle_manifest(&state.bottle),
+    };
+    let latest = match latest {
         Ok(m) => m,
         Err(BottleError::BottleNotFound(_)) => {
             spinner.finish_and_clear();
-            ui::print_warning(&format!(
-                "Could not fetch latest manifest for '{}'",
-                state.bottle
-            ));
-            return Ok(());
+            return Ok(UpdateCheck {
+                latest_version: None,
+                up_to_date: false,
+                changes: Vec::new(),
+                error: Some(format!(
+                    "Could not fetch latest manifest for '{}'",
+                    state.bottle
+                )),
+            });
+        }
+        Err(e @ BottleError::NotCached(_)) => {
+            spinner.finish_and_clear();
+            return Ok(UpdateCheck {
+                latest_version: None,
+                up_to_date: false,
+                changes: Vec::new(),
+                error: Some(e.to_string()),
+            });
+        }
+        // An unreachable source doesn't stop status from reporting
+        Err(e) if state.source.is_some() && !matches!(e, BottleError::SignatureError(_)) => {
+            spinner.finish_and_clear();
+            return Ok(UpdateCheck {
+                latest_version: None,
+                up_to_date: false,
+                changes: Vec::new(),
+                error: Some(e.to_string()),
+            });
         }
         Err(e) => {
             spinner.finish_and_clear();
@@ -129,53 +405,61 @@ fn check_for_updates(state: &BottleState) -> Result<()> {
 
     spinner.finish_and_clear();
 
+    Ok(UpdateCheck {
+        up_to_date: latest.version == state.bottle_version,
+        changes: calculate_changes(state, &resolve_versions(&latest, None)),
+        latest_version: Some(latest.version),
+        error: None,
+    })
+}
+
+/// Render the update check for humans
+fn show_update_check(bottle: &str, update: &UpdateCheck) {
+    if let Some(error) = &update.error {
+        ui::print_warning(error);
+        return;
+    }
+
     // Compare versions
-    if latest.version == state.bottle_version {
+    if update.up_to_date {
         println!("{}", style("Up to date.").green());
-        return Ok(());
+        return;
     }
 
     // Show update info
     println!(
         "{}: {} ({})",
         style("Update available").yellow().bold(),
-        state.bottle,
-        latest.version
+        bottle,
+        update.latest_version.as_deref().unwrap_or("unknown")
     );
     println!("  Changes:");
 
-    // Compare tool versions
-    for (tool, new_version) in &latest.tools {
-        if let Some(tool_state) = state.tools.get(tool) {
-            if &tool_state.version != new_version {
+    for planned in &update.changes {
+        match &planned.change {
+            ToolChange::Upgrade { from, to } => {
                 println!(
                     "    {:<12} {} {} {}",
-                    tool,
-                    tool_state.version,
+                    planned.tool,
+                    from,
                     style("->").dim(),
-                    style(new_version).green()
+                    style(to).green()
                 );
             }
-        } else {
-            // New tool added
-            println!(
-                "    {:<12} {} {}",
-                tool,
-                style("(new)").dim(),
-                style(new_version).green()
-            );
-        }
-    }
-
-    // Check for removed tools
-    for tool in state.tools.keys() {
-        if !latest.tools.contains_key(tool) {
-            println!("    {:<12} {}", tool, style("(removed)").red());
+            ToolChange::Add { version } => {
+                println!(
+                    "    {:<12} {} {}",
+                    planned.tool,
+                    style("(new)").dim(),
+                    style(version).green()
+                );
+            }
+            ToolChange::Remove => {
+                println!("    {:<12} {}", planned.tool, style("(removed)").red());
+            }
         }
     }
 
     println!();
     println!("Run {} to upgrade", style("bottle update").cyan());
-
-    Ok(())
 }
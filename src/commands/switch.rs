@@ -1,21 +1,43 @@
 use super::common::{
-    build_agents_md_snippet, check_prerequisites, fetch_or_load_manifest, MARKETPLACE,
+    build_agents_md_snippet, check_prerequisites, fetch_or_load_manifest, fetch_shared_bottle,
+    install_tool_jobs, load_lock, lock_path, record_history, recorded_source,
+    require_dry_run_for_json, resolve_versions, SharedBottle, MARKETPLACE,
 };
+use super::transaction::{run_strict, Transaction};
 use crate::error::{BottleError, Result};
-use crate::fetch::fetch_tool_definition;
-use crate::install::{self, mcp, plugin};
-use crate::manifest::bottle::BottleManifest;
+use crate::install::{log, mcp, parallel::Job, plugin};
+use crate::manifest::bottle::{BottleManifest, BrewPolicy};
+use crate::manifest::history::Operation;
 use crate::manifest::state::{BottleState, InstallMethod, Mode, ToolState};
-use crate::ui;
+use crate::ui::{self, OutputFormat};
+use crate::version;
 use chrono::Utc;
 use console::style;
+use serde::Serialize;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
 /// Switch to a different bottle
-pub fn run(bottle: &str, yes: bool) -> Result<()> {
+pub fn run(
+    bottle: &str,
+    yes: bool,
+    dry_run: bool,
+    strict: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    require_dry_run_for_json(format, dry_run)?;
+
     // 1. Load current state
     let state = BottleState::load().ok_or(BottleError::NoBottleInstalled)?;
 
+    // A shared bottle (manifest URL, gh: path or git repository) goes by the name in
+    // its manifest
+    let shared = fetch_shared_bottle(bottle)?;
+    let bottle = match &shared {
+        Some(shared) => shared.manifest.name.as_str(),
+        None => bottle,
+    };
+
     // 2. Check if already on this bottle
     if state.bottle == bottle && state.is_managed() {
         ui::print_warning(&format!(
@@ -33,75 +55,170 @@ pub fn run(bottle: &str, yes: bool) -> Result<()> {
         ));
     }
 
-    // 4. Fetch new bottle manifest
-    let spinner = ui::spinner("Fetching bottle manifest...");
-    let new_manifest = fetch_or_load_manifest(bottle, None)?;
-    spinner.finish_and_clear();
+    // 4. Fetch new bottle manifest (a shared one is saved as a bespoke bottle once the
+    // switch has been applied)
+    let (new_manifest, source) = match &shared {
+        Some(shared) => {
+            shared.check()?;
+            (shared.manifest.clone(), Some(shared.source.to_string()))
+        }
+        None => {
+            let spinner = ui::spinner("Fetching bottle manifest...");
+            let manifest = fetch_or_load_manifest(bottle, None)?;
+            spinner.finish_and_clear();
+            (manifest, recorded_source(bottle))
+        }
+    };
+
+    // "latest" switches to what the bottle's lock file recorded, or the newest release
+    let locked = lock_path(bottle, None).as_deref().and_then(load_lock);
+    let new_manifest = resolve_versions(&new_manifest, locked.as_ref());
 
     // 5. Check prerequisites
     check_prerequisites(&new_manifest)?;
 
+    // 6-11. Plan, confirm and apply the switch
+    if !apply_switch(
+        &state,
+        &new_manifest,
+        source,
+        shared.as_ref(),
+        Operation::Switch,
+        yes,
+        dry_run,
+        strict,
+        format,
+    )? {
+        return Ok(());
+    }
+
+    // 12. Show success
+    show_success(&state.bottle, &new_manifest);
+
+    Ok(())
+}
+
+/// Plan, confirm and apply a move from `state` to `new_manifest` (`source` is where a
+/// shared bottle came from; `shared` is saved once its tools are in place). Shared by
+/// `switch` and `rollback`; returns false if nothing was applied (dry run).
+#[allow(clippy::too_many_arguments)]
+pub(super) fn apply_switch(
+    state: &BottleState,
+    new_manifest: &BottleManifest,
+    source: Option<String>,
+    shared: Option<&SharedBottle>,
+    operation: Operation,
+    yes: bool,
+    dry_run: bool,
+    strict: bool,
+    format: OutputFormat,
+) -> Result<bool> {
     // 6. Calculate and show switch plan
-    let plan = calculate_switch_plan(&state, &new_manifest);
-    show_switch_plan(&state.bottle, &new_manifest, &plan);
+    let plan = calculate_switch_plan(state, new_manifest);
+
+    if format.is_json() {
+        ui::print_json(operation.name(), &plan)?;
+        return Ok(false);
+    }
+
+    if dry_run {
+        println!();
+        println!("{}", style("[DRY RUN]").yellow().bold());
+        show_switch_plan(new_manifest, &plan);
+        println!("{}", style("No changes made.").dim());
+        println!();
+        return Ok(false);
+    }
+
+    show_switch_plan(new_manifest, &plan);
 
     // 7. Confirm (unless -y)
-    if !yes && !ui::confirm("Proceed with switch?", true) {
+    let prompt = format!("Proceed with {}?", operation.name());
+    if !yes && !ui::confirm(&prompt, true) {
         return Err(BottleError::Cancelled);
     }
 
     println!();
 
-    // 8. Execute the switch
-    let tool_states = execute_switch(&state, &plan)?;
-
-    // 9. Handle plugins
-    update_plugins(&new_manifest)?;
+    // 8-11. Execute the switch and save state (all-or-nothing with --strict)
+    // AIDEV-NOTE: Plugins aren't journaled by the transaction, so they are installed
+    // only after every step that can fail (and roll the switch back) has succeeded.
+    let tx = strict.then(|| Transaction::begin(&new_manifest.name, Some(state)));
+    run_strict(tx, |tx| {
+        // 8. Execute the switch
+        let tool_states = execute_switch(state, &plan, new_manifest.brew_policy, tx)?;
+        if let Some(shared) = shared {
+            shared.save()?;
+        }
 
-    // 10. Build snippet for new bottle (if any)
-    let snippet = match build_agents_md_snippet(&new_manifest) {
-        Ok(s) => s,
-        Err(e) => {
-            ui::print_warning(&format!("Failed to build AGENTS.md snippet: {}", e));
-            None
+        // 9. Build snippet for new bottle (if any)
+        let snippet = match build_agents_md_snippet(new_manifest) {
+            Ok(s) => s,
+            Err(e) => {
+                ui::print_warning(&format!("Failed to build AGENTS.md snippet: {}", e));
+                None
+            }
+        };
+
+        // 10. Save new state (preserve integrations and custom tools across bottle switches)
+        let new_state = BottleState {
+            bottle: new_manifest.name.clone(),
+            bottle_version: new_manifest.version.clone(),
+            installed_at: Utc::now(),
+            tools: tool_states,
+            mode: Mode::Managed,
+            integrations: state.integrations.clone(),
+            custom_tools: state.custom_tools.clone(),
+            source,
+        };
+        new_state
+            .save()
+            .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+
+        // Save snippet alongside state if present
+        if let Some(snippet_content) = &snippet {
+            new_state.save_snippet(snippet_content).map_err(|e| {
+                BottleError::Other(format!("Failed to save AGENTS.md snippet: {}", e))
+            })?;
         }
-    };
 
-    // 11. Save new state (preserve integrations and custom tools across bottle switches)
-    let new_state = BottleState {
-        bottle: new_manifest.name.clone(),
-        bottle_version: new_manifest.version.clone(),
-        installed_at: Utc::now(),
-        tools: tool_states,
-        mode: Mode::Managed,
-        integrations: state.integrations.clone(),
-        custom_tools: state.custom_tools.clone(),
-    };
-    new_state
-        .save()
-        .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+        // 11. Handle plugins
+        update_plugins(new_manifest)?;
 
-    // Save snippet alongside state if present
-    if let Some(snippet_content) = &snippet {
-        new_state
-            .save_snippet(snippet_content)
-            .map_err(|e| BottleError::Other(format!("Failed to save AGENTS.md snippet: {}", e)))?;
-    }
+        record_history(operation, &new_state);
 
-    // 12. Show success
-    show_success(&state.bottle, &new_manifest);
+        Ok(())
+    })?;
 
-    Ok(())
+    Ok(true)
+}
+
+/// A tool at a single version
+#[derive(Debug, Serialize)]
+struct ToolVersion {
+    tool: String,
+    version: String,
+}
+
+/// A tool moving between versions
+#[derive(Debug, Serialize)]
+struct VersionChange {
+    tool: String,
+    from: String,
+    to: String,
 }
 
 /// What changes need to be made
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 struct SwitchPlan {
-    add: Vec<(String, String)>,               // (tool, version)
-    remove: Vec<String>,                      // tool name
-    upgrade: Vec<(String, String, String)>,   // (tool, old_version, new_version)
-    downgrade: Vec<(String, String, String)>, // (tool, old_version, new_version)
-    unchanged: Vec<(String, String)>,         // (tool, version)
+    from_bottle: String,
+    to_bottle: String,
+    to_version: String,
+    add: Vec<ToolVersion>,
+    remove: Vec<String>,
+    upgrade: Vec<VersionChange>,
+    downgrade: Vec<VersionChange>,
+    unchanged: Vec<ToolVersion>,
 }
 
 /// Calculate what changes are needed to switch bottles
@@ -110,6 +227,9 @@ fn calculate_switch_plan(state: &BottleState, new_manifest: &BottleManifest) ->
     let new_tools: HashSet<&str> = new_manifest.tools.keys().map(|s| s.as_str()).collect();
 
     let mut plan = SwitchPlan {
+        from_bottle: state.bottle.clone(),
+        to_bottle: new_manifest.name.clone(),
+        to_version: new_manifest.version.clone(),
         add: Vec::new(),
         remove: Vec::new(),
         upgrade: Vec::new(),
@@ -120,7 +240,10 @@ fn calculate_switch_plan(state: &BottleState, new_manifest: &BottleManifest) ->
     // Tools to add (in new but not in current)
     for tool in new_tools.difference(&current_tools) {
         let version = new_manifest.tools.get(*tool).unwrap();
-        plan.add.push((tool.to_string(), version.clone()));
+        plan.add.push(ToolVersion {
+            tool: tool.to_string(),
+            version: version.clone(),
+        });
     }
 
     // Tools to remove (in current but not in new)
@@ -133,82 +256,61 @@ fn calculate_switch_plan(state: &BottleState, new_manifest: &BottleManifest) ->
         let current_version = &state.tools.get(*tool).unwrap().version;
         let new_version = new_manifest.tools.get(*tool).unwrap();
 
-        if current_version == new_version {
-            plan.unchanged
-                .push((tool.to_string(), current_version.clone()));
+        // "latest" or a range that could not be resolved (offline) keeps what is
+        // installed if that satisfies it, as in update
+        if current_version == new_version || version::satisfies(new_version, current_version) {
+            plan.unchanged.push(ToolVersion {
+                tool: tool.to_string(),
+                version: current_version.clone(),
+            });
         } else {
-            // Compare versions (simple string comparison for semver)
-            match compare_versions(current_version, new_version) {
-                std::cmp::Ordering::Less => {
-                    plan.upgrade.push((
-                        tool.to_string(),
-                        current_version.clone(),
-                        new_version.clone(),
-                    ));
+            // A version that can't be ordered is treated as an upgrade
+            match version::compare(current_version, new_version) {
+                Some(Ordering::Less) | None => {
+                    plan.upgrade.push(VersionChange {
+                        tool: tool.to_string(),
+                        from: current_version.clone(),
+                        to: new_version.clone(),
+                    });
                 }
-                std::cmp::Ordering::Greater => {
-                    plan.downgrade.push((
-                        tool.to_string(),
-                        current_version.clone(),
-                        new_version.clone(),
-                    ));
+                Some(Ordering::Greater) => {
+                    plan.downgrade.push(VersionChange {
+                        tool: tool.to_string(),
+                        from: current_version.clone(),
+                        to: new_version.clone(),
+                    });
                 }
-                std::cmp::Ordering::Equal => {
-                    plan.unchanged
-                        .push((tool.to_string(), current_version.clone()));
+                Some(Ordering::Equal) => {
+                    plan.unchanged.push(ToolVersion {
+                        tool: tool.to_string(),
+                        version: current_version.clone(),
+                    });
                 }
             }
         }
     }
 
     // Sort all vectors for consistent output
-    plan.add.sort_by(|a, b| a.0.cmp(&b.0));
+    plan.add.sort_by(|a, b| a.tool.cmp(&b.tool));
     plan.remove.sort();
-    plan.upgrade.sort_by(|a, b| a.0.cmp(&b.0));
-    plan.downgrade.sort_by(|a, b| a.0.cmp(&b.0));
-    plan.unchanged.sort_by(|a, b| a.0.cmp(&b.0));
+    plan.upgrade.sort_by(|a, b| a.tool.cmp(&b.tool));
+    plan.downgrade.sort_by(|a, b| a.tool.cmp(&b.tool));
+    plan.unchanged.sort_by(|a, b| a.tool.cmp(&b.tool));
 
     plan
 }
 
-/// Simple semver comparison (numeric parts only)
-/// Note: Pre-release suffixes (e.g., "-beta", "-rc1") are ignored.
-/// Versions like "1.2.3-beta" and "1.2.3" compare as equal.
-fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
-    let parse = |v: &str| -> Vec<u32> {
-        v.split('.')
-            .filter_map(|part| {
-                // Strip pre-release suffix (e.g., "3-beta" -> "3")
-                let numeric = part.split('-').next().unwrap_or(part);
-                numeric.parse::<u32>().ok()
-            })
-            .collect()
-    };
-
-    let va = parse(a);
-    let vb = parse(b);
-
-    for (pa, pb) in va.iter().zip(vb.iter()) {
-        match pa.cmp(pb) {
-            std::cmp::Ordering::Equal => continue,
-            other => return other,
-        }
-    }
-
-    va.len().cmp(&vb.len())
-}
-
 /// Display the switch plan
-fn show_switch_plan(from_bottle: &str, to_manifest: &BottleManifest, plan: &SwitchPlan) {
+fn show_switch_plan(to_manifest: &BottleManifest, plan: &SwitchPlan) {
     println!();
     println!(
         "{} {} ({}) {} {} ({})",
         style("Switching from").bold(),
-        style(from_bottle).cyan(),
+        style(&plan.from_bottle).cyan(),
         style("current").dim(),
         style("→").bold(),
-        style(&to_manifest.name).cyan(),
-        &to_manifest.version
+        style(&plan.to_bottle).cyan(),
+        &plan.to_version
     );
     println!("{}", style(&to_manifest.description).dim());
     println!();
@@ -231,12 +333,12 @@ fn show_switch_plan(from_bottle: &str, to_manifest: &BottleManifest, plan: &Swit
     println!("{}:", style("Changes").bold());
 
     // New tools
-    for (tool, version) in &plan.add {
+    for entry in &plan.add {
         println!(
             "  {} {:<12} {}",
             style("+").green().bold(),
-            tool,
-            style(version).dim()
+            entry.tool,
+            style(&entry.version).dim()
         );
     }
 
@@ -246,24 +348,24 @@ fn show_switch_plan(from_bottle: &str, to_manifest: &BottleManifest, plan: &Swit
     }
 
     // Upgrades
-    for (tool, old, new) in &plan.upgrade {
+    for change in &plan.upgrade {
         println!(
             "  {} {:<12} {} → {}",
             style("↑").blue().bold(),
-            tool,
-            style(old).dim(),
-            style(new).green()
+            change.tool,
+            style(&change.from).dim(),
+            style(&change.to).green()
         );
     }
 
     // Downgrades
-    for (tool, old, new) in &plan.downgrade {
+    for change in &plan.downgrade {
         println!(
             "  {} {:<12} {} → {}",
             style("↓").yellow().bold(),
-            tool,
-            style(old).dim(),
-            style(new).yellow()
+            change.tool,
+            style(&change.from).dim(),
+            style(&change.to).yellow()
         );
     }
 
@@ -283,13 +385,19 @@ fn show_switch_plan(from_bottle: &str, to_manifest: &BottleManifest, plan: &Swit
 /// AIDEV-NOTE: Intentionally continues on failure and returns Ok with partial results.
 /// State tracks what succeeded. User sees warnings for failures and can retry.
 /// This is a design decision to avoid leaving users in a broken state when one
-/// tool fails but others succeed.
-fn execute_switch(state: &BottleState, plan: &SwitchPlan) -> Result<HashMap<String, ToolState>> {
+/// tool fails but others succeed. With --strict (`tx` is Some) the first failure
+/// is returned instead so the caller can roll back.
+fn execute_switch(
+    state: &BottleState,
+    plan: &SwitchPlan,
+    brew_policy: BrewPolicy,
+    mut tx: Option<&mut Transaction>,
+) -> Result<HashMap<String, ToolState>> {
     let mut tool_states: HashMap<String, ToolState> = HashMap::new();
     let mut failures: Vec<(String, BottleError)> = Vec::new();
 
     // Copy unchanged tools to new state
-    for (tool, version) in &plan.unchanged {
+    for ToolVersion { tool, version } in &plan.unchanged {
         if let Some(existing) = state.tools.get(tool) {
             tool_states.insert(tool.clone(), existing.clone());
         } else {
@@ -299,6 +407,8 @@ fn execute_switch(state: &BottleState, plan: &SwitchPlan) -> Result<HashMap<Stri
                     version: version.clone(),
                     installed_at: Utc::now(),
                     method: InstallMethod::Cargo, // Default
+                    observed_version: None,
+                    brew: None,
                 },
             );
         }
@@ -307,36 +417,22 @@ fn execute_switch(state: &BottleState, plan: &SwitchPlan) -> Result<HashMap<Stri
     // Install new tools
     if !plan.add.is_empty() {
         println!("{}:", style("Installing new tools").bold());
-        for (tool, version) in &plan.add {
-            print!("  {:<12} {} ", tool, style(version).dim());
-
-            let tool_def = match fetch_tool_definition(tool) {
-                Ok(def) => def,
-                Err(e) => {
-                    println!("{}", style("failed").red());
-                    failures.push((tool.clone(), e));
-                    continue;
-                }
-            };
-
-            match install::install_tool(&tool_def, version) {
-                Ok(method) => {
-                    println!("{}", style("installed").green());
-                    tool_states.insert(
-                        tool.clone(),
-                        ToolState {
-                            version: version.clone(),
-                            installed_at: Utc::now(),
-                            method,
-                        },
-                    );
-                }
-                Err(e) => {
-                    println!("{}", style("failed").red());
-                    failures.push((tool.clone(), e));
-                }
-            }
-        }
+        let jobs: Vec<Job> = plan
+            .add
+            .iter()
+            .map(|ToolVersion { tool, version }| Job {
+                tool: tool.clone(),
+                version: version.clone(),
+                done: "installed",
+            })
+            .collect();
+        install_tool_jobs(
+            &jobs,
+            brew_policy,
+            &mut tool_states,
+            &mut failures,
+            tx.as_deref_mut(),
+        )?;
         println!();
     }
 
@@ -345,41 +441,27 @@ fn execute_switch(state: &BottleState, plan: &SwitchPlan) -> Result<HashMap<Stri
 
     if !version_changes.is_empty() {
         println!("{}:", style("Updating tools").bold());
-        for (tool, _old, new) in &version_changes {
-            print!("  {:<12} {} ", tool, style(new).dim());
-
-            let tool_def = match fetch_tool_definition(tool) {
-                Ok(def) => def,
-                Err(e) => {
-                    println!("{}", style("failed").red());
-                    failures.push((tool.clone(), e));
-                    // Keep old state on failure
-                    if let Some(existing) = state.tools.get(tool) {
-                        tool_states.insert(tool.clone(), existing.clone());
-                    }
-                    continue;
-                }
-            };
-
-            match install::install_tool(&tool_def, new) {
-                Ok(method) => {
-                    println!("{}", style("updated").green());
-                    tool_states.insert(
-                        tool.clone(),
-                        ToolState {
-                            version: new.clone(),
-                            installed_at: Utc::now(),
-                            method,
-                        },
-                    );
-                }
-                Err(e) => {
-                    println!("{}", style("failed").red());
-                    failures.push((tool.clone(), e));
-                    // Keep old state on failure
-                    if let Some(existing) = state.tools.get(tool) {
-                        tool_states.insert(tool.clone(), existing.clone());
-                    }
+        let jobs: Vec<Job> = version_changes
+            .iter()
+            .map(|VersionChange { tool, to, .. }| Job {
+                tool: tool.clone(),
+                version: to.clone(),
+                done: "updated",
+            })
+            .collect();
+        install_tool_jobs(
+            &jobs,
+            brew_policy,
+            &mut tool_states,
+            &mut failures,
+            tx.as_deref_mut(),
+        )?;
+
+        // Keep old state for tools that failed to update
+        for VersionChange { tool, .. } in &version_changes {
+            if !tool_states.contains_key(tool) {
+                if let Some(existing) = state.tools.get(tool) {
+                    tool_states.insert(tool.clone(), existing.clone());
                 }
             }
         }
@@ -402,12 +484,18 @@ fn execute_switch(state: &BottleState, plan: &SwitchPlan) -> Result<HashMap<Stri
             println!("{}:", style("Unregistering MCP servers").bold());
             for tool in &mcp_tools {
                 print!("  {:<12} ", tool);
-                match mcp::unregister(tool) {
+                match log::capture(tool, true, || mcp::unregister(tool)) {
                     Ok(()) => {
                         println!("{}", style("removed").green());
+                        if let Some(tx) = tx.as_deref_mut() {
+                            tx.record_mcp_unregistered(tool);
+                        }
                     }
                     Err(e) => {
                         println!("{}", style("failed").red());
+                        if tx.is_some() {
+                            return Err(e);
+                        }
                         failures.push((tool.to_string(), e));
                     }
                 }
@@ -0,0 +1,240 @@
+use super::install::uninstall_custom_tool;
+use crate::error::BottleError;
+use crate::fetch::fetch_tool_definition;
+use crate::install::{self, log, mcp};
+use crate::manifest::bottle::{BrewPolicy, CustomToolDef};
+use crate::manifest::state::{BottleState, CustomInstallMethod, InstallMethod, ToolState};
+use crate::ui;
+use console::style;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Journal for a `--strict` install, update or switch
+/// AIDEV-NOTE: Strict mode is all-or-nothing. The commands stop at the first failed
+/// step and `rollback` undoes every journaled step in reverse order, then restores
+/// the state files byte-for-byte. Tools that were already present but untracked by
+/// bottle are never uninstalled, since we can't know what version they were at.
+pub(super) struct Transaction {
+    /// Tool states before the operation (from the active bottle)
+    previous_tools: HashMap<String, ToolState>,
+    /// Raw files that the operation may overwrite, restored verbatim on rollback
+    files: Vec<(PathBuf, Option<String>)>,
+    /// Active bottle before the operation
+    previous_active: Option<String>,
+    steps: Vec<Step>,
+}
+
+/// A completed step that rollback knows how to undo
+enum Step {
+    /// Curated tool installed, upgraded or downgraded
+    Tool {
+        name: String,
+        method: InstallMethod,
+        previous: Option<ToolState>,
+        preexisting: bool,
+    },
+    /// Curated MCP server unregistered because the new bottle drops it
+    McpUnregistered { name: String, previous: ToolState },
+    /// Bespoke MCP server registered
+    McpServer { name: String, preexisting: bool },
+    /// Custom tool installed from a bespoke manifest
+    CustomTool {
+        name: String,
+        def: CustomToolDef,
+        method: CustomInstallMethod,
+        preexisting: bool,
+    },
+}
+
+impl Transaction {
+    /// Snapshot state before changing `target` (the bottle whose state will be written)
+    pub fn begin(target: &str, current: Option<&BottleState>) -> Self {
+        let previous_active = BottleState::active_bottle();
+
+        let files = [
+            BottleState::state_path(target),
+            BottleState::snippet_path(target),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|path| {
+            let contents = std::fs::read_to_string(&path).ok();
+            (path, contents)
+        })
+        .collect();
+
+        Self {
+            previous_tools: current.map(|s| s.tools.clone()).unwrap_or_default(),
+            files,
+            previous_active,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Record a curated tool install (call `install::is_present` before installing)
+    pub fn record_tool(&mut self, name: &str, method: InstallMethod, preexisting: bool) {
+        self.steps.push(Step::Tool {
+            name: name.to_string(),
+            method,
+            previous: self.previous_tools.get(name).cloned(),
+            preexisting,
+        });
+    }
+
+    /// Record that a curated MCP server was unregistered
+    pub fn record_mcp_unregistered(&mut self, name: &str) {
+        if let Some(previous) = self.previous_tools.get(name).cloned() {
+            self.steps.push(Step::McpUnregistered {
+                name: name.to_string(),
+                previous,
+            });
+        }
+    }
+
+    /// Record a bespoke MCP server registration
+    pub fn record_mcp_server(&mut self, name: &str, preexisting: bool) {
+        self.steps.push(Step::McpServer {
+            name: name.to_string(),
+            preexisting,
+        });
+    }
+
+    /// Record a custom tool install
+    pub fn record_custom_tool(
+        &mut self,
+        name: &str,
+        def: &CustomToolDef,
+        method: CustomInstallMethod,
+        preexisting: bool,
+    ) {
+        self.steps.push(Step::CustomTool {
+            name: name.to_string(),
+            def: def.clone(),
+            method,
+            preexisting,
+        });
+    }
+
+    /// Undo all recorded steps and restore state files, returning the error to report
+    pub fn rollback(self, cause: BottleError) -> BottleError {
+        println!();
+        ui::print_warning(&format!("Strict mode: {}", cause));
+        println!("{}:", style("Rolling back").bold());
+
+        let mut failures = 0;
+
+        for step in self.steps.iter().rev() {
+            let (name, action, result) = match step {
+                Step::Tool {
+                    name,
+                    previous: Some(previous),
+                    ..
+                }
+                | Step::McpUnregistered { name, previous } => (
+                    name,
+                    format!("restore {}", previous.version),
+                    log::capture(name, true, || {
+                        let def = fetch_tool_definition(name)?;
+                        install::install_tool(&def, &previous.version, BrewPolicy::Warn)?;
+                        Ok(())
+                    }),
+                ),
+                Step::Tool {
+                    name,
+                    method,
+                    previous: None,
+                    preexisting: false,
+                } => (
+                    name,
+                    "uninstall".to_string(),
+                    log::capture(name, true, || {
+                        install::uninstall_tool(&fetch_tool_definition(name)?, method)
+                    }),
+                ),
+                Step::McpServer {
+                    name,
+                    preexisting: false,
+                } => (
+                    name,
+                    "unregister".to_string(),
+                    log::capture(name, true, || mcp::unregister(name)),
+                ),
+                Step::CustomTool {
+                    name,
+                    def,
+                    method,
+                    preexisting: false,
+                } => (
+                    name,
+                    "uninstall".to_string(),
+                    log::capture(name, true, || uninstall_custom_tool(name, def, method)),
+                ),
+                // Already present before we started - leave as found
+                Step::Tool { .. } | Step::McpServer { .. } | Step::CustomTool { .. } => continue,
+            };
+
+            print!("  {:<12} {} ", name, style(&action).dim());
+            match result {
+                Ok(()) => println!("{}", style("ok").green()),
+                Err(e) => {
+                    println!("{}", style("failed").red());
+                    ui::print_warning(&format!("{}: {}", name, e));
+                    failures += 1;
+                }
+            }
+        }
+
+        print!("  {:<12} ", "state");
+        match self.restore_files() {
+            Ok(()) => println!("{}", style("restored").green()),
+            Err(e) => {
+                println!("{}", style("failed").red());
+                ui::print_warning(&format!("Could not restore state: {}", e));
+                failures += 1;
+            }
+        }
+        println!();
+
+        if failures == 0 {
+            BottleError::RolledBack(cause.to_string())
+        } else {
+            BottleError::RolledBack(format!(
+                "{} ({} rollback step(s) also failed, see above)",
+                cause, failures
+            ))
+        }
+    }
+
+    /// Put state files and the active pointer back exactly as they were
+    fn restore_files(&self) -> std::io::Result<()> {
+        for (path, contents) in &self.files {
+            match contents {
+                Some(contents) => std::fs::write(path, contents)?,
+                None if path.exists() => std::fs::remove_file(path)?,
+                None => {}
+            }
+        }
+
+        match &self.previous_active {
+            Some(bottle) => BottleState::set_active(bottle)?,
+            // Nothing was active: drop the pointer and legacy symlink we created
+            None => BottleState::clear_active()?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Run `body` with an optional transaction, rolling back if it fails
+pub(super) fn run_strict<T>(
+    mut tx: Option<Transaction>,
+    body: impl FnOnce(Option<&mut Transaction>) -> crate::error::Result<T>,
+) -> crate::error::Result<T> {
+    match body(tx.as_mut()) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(match tx {
+            Some(tx) => tx.rollback(e),
+            None => e,
+        }),
+    }
+}
@@ -0,0 +1,478 @@
+use super::common::{fetch_or_load_manifest, require_dry_run_for_json};
+use super::install::uninstall_custom_tool;
+use super::integrate::describe_remove_action;
+use crate::error::{BottleError, Result};
+use crate::fetch::fetch_tool_definition;
+use crate::install::{self, log, mcp};
+use crate::integrate::{self, Platform};
+use crate::manifest::bottle::{BottleManifest, CustomToolDef};
+use crate::manifest::project::LOCK_FILE;
+use crate::manifest::state::{BottleState, CustomInstallMethod, InstallMethod};
+use crate::ui::{self, OutputFormat};
+use console::style;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Files kept when removing a bespoke bottle's directory: the user's own manifest and
+/// lock, and where a shared bottle was fetched from (so `update` still follows it)
+const BESPOKE_KEEP: &[&str] = &[
+    "manifest.json",
+    "manifest.json.minisig",
+    "source",
+    LOCK_FILE,
+];
+
+/// Remove the active bottle: tools, MCP servers, integrations and state
+///
+/// With `keep_tools`, installed binaries and packages are left in place; MCP servers
+/// and integrations are still removed since they only make sense under bottle.
+pub fn run(keep_tools: bool, yes: bool, dry_run: bool, format: OutputFormat) -> Result<()> {
+    require_dry_run_for_json(format, dry_run)?;
+
+    // 1. Load current state
+    let state = BottleState::load().ok_or(BottleError::NoBottleInstalled)?;
+
+    // 2. Load the manifest for custom tool and MCP server details (best effort)
+    let manifest = match fetch_or_load_manifest(&state.bottle, None) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            ui::eprint_warning(&format!(
+                "Could not load the {} manifest ({}); custom tools other than binaries and \
+                 bespoke MCP servers cannot be removed",
+                state.bottle, e
+            ));
+            None
+        }
+    };
+
+    // 3. Build the plan
+    let plan = UninstallPlan::build(&state, manifest.as_ref(), keep_tools);
+
+    if format.is_json() {
+        return ui::print_json("uninstall", &plan);
+    }
+
+    if dry_run {
+        println!();
+        println!("{}", style("[DRY RUN]").yellow().bold());
+        show_plan(&plan);
+        println!("{}", style("No changes made.").dim());
+        println!();
+        return Ok(());
+    }
+
+    show_plan(&plan);
+
+    // 4. Confirm (unless -y)
+    if !yes && !ui::confirm(&format!("Uninstall {}?", plan.bottle), false) {
+        return Err(BottleError::Cancelled);
+    }
+
+    println!();
+
+    // 5. Remove everything, continuing past failures
+    let mut remaining = state.clone();
+    let failures = execute_plan(&plan, &mut remaining, manifest.as_ref());
+
+    // 6. Keep state for anything that could not be removed so uninstall can be retried
+    if !failures.is_empty() {
+        remaining
+            .save()
+            .map_err(|e| BottleError::Other(format!("Failed to save remaining state: {}", e)))?;
+
+        ui::print_warning(&format!("{} removal(s) failed:", failures.len()));
+        for (name, err) in &failures {
+            println!("  {} - {}", style(name).red(), err);
+        }
+        println!();
+        return Err(BottleError::Other(format!(
+            "{} was only partially uninstalled. Fix the errors above and run `bottle uninstall` again.",
+            plan.bottle
+        )));
+    }
+
+    // 7. Remove state and the bottle directory
+    remove_bottle_dir(&state.bottle, plan.bespoke)?;
+    if BottleState::active_bottle().as_deref() == Some(state.bottle.as_str()) {
+        BottleState::clear_active()?;
+    }
+
+    ui::print_success(&format!("Uninstalled {}", plan.bottle));
+    if plan.bespoke {
+        println!();
+        println!(
+            "Kept the bespoke manifest. Run {} to install it again.",
+            style(format!("bottle install {}", plan.bottle)).cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// One thing to remove and how
+#[derive(Debug, Serialize)]
+struct Removal {
+    name: String,
+    version: Option<String>,
+    action: String,
+}
+
+/// Everything `bottle uninstall` will do
+#[derive(Debug, Serialize)]
+struct UninstallPlan {
+    bottle: String,
+    bottle_version: String,
+    keep_tools: bool,
+    /// Bespoke bottles keep their manifest
+    bespoke: bool,
+    integrations: Vec<Removal>,
+    mcp_servers: Vec<Removal>,
+    tools: Vec<Removal>,
+    custom_tools: Vec<Removal>,
+    kept_tools: Vec<String>,
+    remove_dir: Option<PathBuf>,
+}
+
+impl UninstallPlan {
+    fn build(state: &BottleState, manifest: Option<&BottleManifest>, keep_tools: bool) -> Self {
+        let bespoke = BottleState::bottle_path(&state.bottle)
+            .map(|d| d.join("manifest.json").exists())
+            .unwrap_or(false);
+
+        let mut integrations: Vec<Removal> = state
+            .integrations
+            .keys()
+            .map(|key| Removal {
+                name: key.clone(),
+                version: None,
+                action: Platform::from_key(key)
+                    .map(describe_remove_action)
+                    .unwrap_or("Unknown integration, drop from state")
+                    .to_string(),
+            })
+            .collect();
+        integrations.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // MCP servers are removed even with --keep-tools
+        let mut mcp_servers: Vec<Removal> = state
+            .tools
+            .iter()
+            .filter(|(_, t)| matches!(t.method, InstallMethod::Mcp))
+            .map(|(name, t)| Removal {
+                name: name.clone(),
+                version: Some(t.version.clone()),
+                action: format!("claude mcp remove {}", name),
+            })
+            .collect();
+        if let Some(manifest) = manifest {
+            mcp_servers.extend(
+                manifest
+                    .mcp_servers
+                    .keys()
+                    .filter(|name| mcp::is_registered(name))
+                    .map(|name| Removal {
+                        name: name.clone(),
+                        version: None,
+                        action: format!("claude mcp remove {}", name),
+                    }),
+            );
+        }
+        mcp_servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut tools = Vec::new();
+        let mut custom_tools = Vec::new();
+        let mut kept_tools = Vec::new();
+
+        for (name, tool) in &state.tools {
+            if matches!(tool.method, InstallMethod::Mcp) {
+                continue;
+            }
+            if keep_tools {
+                kept_tools.push(name.clone());
+            } else {
+                let def = fetch_tool_definition(name).ok();
+                let package = def
+                    .as_ref()
+                    .map(|d| d.package.clone())
+                    .unwrap_or_else(|| name.clone());
+                let action = match tool.method {
+                    InstallMethod::Brew => format!("brew uninstall {}", package),
+                    InstallMethod::Prebuilt => format!(
+                        "delete ~/.local/bin/{}",
+                        def.as_ref()
+                            .map(install::prebuilt::binary_name)
+                            .unwrap_or_else(|| name.clone())
+                    ),
+                    _ => format!("cargo uninstall {}", package),
+                };
+                tools.push(Removal {
+                    name: name.clone(),
+                    version: Some(tool.version.clone()),
+                    action,
+                });
+            }
+        }
+
+        for (name, tool) in &state.custom_tools {
+            if keep_tools {
+                kept_tools.push(name.clone());
+                continue;
+            }
+            let def = manifest.and_then(|m| m.custom_tools.get(name));
+            custom_tools.push(Removal {
+                name: name.clone(),
+                version: Some(tool.version.clone()),
+                action: describe_custom_removal(name, &tool.method, def),
+            });
+        }
+
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        custom_tools.sort_by(|a, b| a.name.cmp(&b.name));
+        kept_tools.sort();
+
+        UninstallPlan {
+            bottle: state.bottle.clone(),
+            bottle_version: state.bottle_version.clone(),
+            keep_tools,
+            bespoke,
+            integrations,
+            mcp_servers,
+            tools,
+            custom_tools,
+            kept_tools,
+            remove_dir: BottleState::bottle_path(&state.bottle),
+        }
+    }
+}
+
+/// Describe how a custom tool will be removed
+fn describe_custom_removal(
+    name: &str,
+    method: &CustomInstallMethod,
+    def: Option<&CustomToolDef>,
+) -> String {
+    let install = def.map(|d| &d.install);
+    match method {
+        CustomInstallMethod::Binary => install::binary::bin_path(name)
+            .map(|p| format!("delete {}", p.display()))
+            .unwrap_or_else(|| format!("delete ~/.local/bin/{}", name)),
+        CustomInstallMethod::Brew => match install.and_then(|i| i.brew.as_ref()) {
+            Some(formula) => format!("brew uninstall {}", formula),
+            None => "brew uninstall (formula unknown, manifest unavailable)".to_string(),
+        },
+        CustomInstallMethod::Cargo => match install.and_then(|i| i.cargo.as_ref()) {
+            Some(krate) => format!("cargo uninstall {}", krate),
+            None => "cargo uninstall (crate unknown, manifest unavailable)".to_string(),
+        },
+        CustomInstallMethod::Npm => match install.and_then(|i| i.npm.as_ref()) {
+            Some(package) => format!("npm uninstall -g {}", package),
+            None => "npm uninstall -g (package unknown, manifest unavailable)".to_string(),
+        },
+    }
+}
+
+/// Display the uninstall plan
+fn show_plan(plan: &UninstallPlan) {
+    ui::print_bottle_header(&plan.bottle, &plan.bottle_version);
+
+    let sections = [
+        ("Remove integrations", &plan.integrations),
+        ("Unregister MCP servers", &plan.mcp_servers),
+        ("Uninstall tools", &plan.tools),
+        ("Uninstall custom tools", &plan.custom_tools),
+    ];
+
+    for (title, removals) in sections {
+        if removals.is_empty() {
+            continue;
+        }
+        println!("{}:", style(title).bold());
+        for removal in removals {
+            println!(
+                "  {:<14} {:<12} {}",
+                removal.name,
+                removal.version.as_deref().unwrap_or(""),
+                style(&removal.action).dim()
+            );
+        }
+        println!();
+    }
+
+    if !plan.kept_tools.is_empty() {
+        println!("{}:", style("Kept installed (--keep-tools)").dim());
+        for name in &plan.kept_tools {
+            println!("  {}", name);
+        }
+        println!();
+    }
+
+    if let Some(dir) = &plan.remove_dir {
+        println!("{}:", style("Delete").bold());
+        if plan.bespoke {
+            println!(
+                "  {} {}",
+                dir.display(),
+                style("(state only, bespoke manifest is kept)").dim()
+            );
+        } else {
+            println!("  {}", dir.display());
+        }
+        println!();
+    }
+}
+
+/// Run the plan, removing each item from `state` as it goes. Returns the failures.
+fn execute_plan(
+    plan: &UninstallPlan,
+    state: &mut BottleState,
+    manifest: Option<&BottleManifest>,
+) -> Vec<(String, BottleError)> {
+    let mut failures: Vec<(String, BottleError)> = Vec::new();
+
+    // Integrations first so plugins stop referring to tools we are about to remove
+    if !plan.integrations.is_empty() {
+        println!("{}:", style("Removing integrations").bold());
+        for removal in &plan.integrations {
+            print!("  {:<14} ", removal.name);
+            let result = match Platform::from_key(&removal.name) {
+                Some(platform) => integrate::remove(platform),
+                None => Ok(()),
+            };
+            report(&removal.name, result, &mut failures, || {
+                state.integrations.remove(&removal.name);
+            });
+        }
+        println!();
+    }
+
+    if !plan.mcp_servers.is_empty() {
+        println!("{}:", style("Unregistering MCP servers").bold());
+        for removal in &plan.mcp_servers {
+            print!("  {:<14} ", removal.name);
+            let result = log::capture(&removal.name, true, || mcp::unregister(&removal.name));
+            report(&removal.name, result, &mut failures, || {
+                state.tools.remove(&removal.name);
+            });
+        }
+        println!();
+    }
+
+    if !plan.tools.is_empty() {
+        println!("{}:", style("Uninstalling tools").bold());
+        for removal in &plan.tools {
+            print!("  {:<14} ", removal.name);
+            let result = match state.tools.get(&removal.name) {
+                Some(tool) => log::capture(&removal.name, true, || {
+                    uninstall_curated_tool(&removal.name, &tool.method)
+                }),
+                None => Ok(()),
+            };
+            report(&removal.name, result, &mut failures, || {
+                state.tools.remove(&removal.name);
+            });
+        }
+        println!();
+    }
+
+    if !plan.custom_tools.is_empty() {
+        println!("{}:", style("Uninstalling custom tools").bold());
+        for removal in &plan.custom_tools {
+            print!("  {:<14} ", removal.name);
+            let def = manifest.and_then(|m| m.custom_tools.get(&removal.name));
+            let result = match state.custom_tools.get(&removal.name) {
+                Some(tool) => log::capture(&removal.name, true, || {
+                    uninstall_recorded_custom_tool(&removal.name, &tool.method, def)
+                }),
+                None => Ok(()),
+            };
+            report(&removal.name, result, &mut failures, || {
+                state.custom_tools.remove(&removal.name);
+            });
+        }
+        println!();
+    }
+
+    failures
+}
+
+/// Print the outcome of one removal and record it
+fn report(
+    name: &str,
+    result: Result<()>,
+    failures: &mut Vec<(String, BottleError)>,
+    on_success: impl FnOnce(),
+) {
+    match result {
+        Ok(()) => {
+            println!("{}", style("removed").green());
+            on_success();
+        }
+        Err(e) => {
+            println!("{}", style("failed").red());
+            failures.push((name.to_string(), e));
+        }
+    }
+}
+
+/// Uninstall a curated tool using its definition and recorded method
+fn uninstall_curated_tool(name: &str, method: &InstallMethod) -> Result<()> {
+    let tool = fetch_tool_definition(name)?;
+    install::uninstall_tool(&tool, method)
+}
+
+/// Uninstall a custom tool; binaries can be removed without the manifest
+fn uninstall_recorded_custom_tool(
+    name: &str,
+    method: &CustomInstallMethod,
+    def: Option<&CustomToolDef>,
+) -> Result<()> {
+    match (def, method) {
+        (Some(def), _) => uninstall_custom_tool(name, def, method),
+        (None, CustomInstallMethod::Binary) => uninstall_custom_tool(
+            name,
+            &CustomToolDef {
+                install: Default::default(),
+                version: String::new(),
+                verify: None,
+            },
+            method,
+        ),
+        (None, _) => Err(BottleError::UninstallError {
+            tool: name.to_string(),
+            reason: "Manifest unavailable, cannot tell which package to remove".to_string(),
+        }),
+    }
+}
+
+/// Delete the bottle directory, keeping a bespoke bottle's own manifest
+fn remove_bottle_dir(bottle: &str, bespoke: bool) -> Result<()> {
+    let Some(dir) = BottleState::bottle_path(bottle) else {
+        return Ok(());
+    };
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    if !bespoke {
+        return std::fs::remove_dir_all(&dir).map_err(BottleError::IoError);
+    }
+
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if is_kept(&path) {
+            continue;
+        }
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_kept(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| BESPOKE_KEEP.contains(&n))
+}
@@ -1,36 +1,105 @@
-use super::common::build_agents_md_snippet;
+use super::common::{
+    build_agents_md_snippet, check_prerequisites, fetch_or_load_manifest, install_tool_jobs,
+    load_project, lock_path, record_history, refresh_shared_bottle, require_dry_run_for_json,
+    resolve_versions, write_lock,
+};
+use super::transaction::{run_strict, Transaction};
 use crate::error::{BottleError, Result};
-use crate::fetch::{fetch_bottle_manifest, fetch_tool_definition};
-use crate::install;
+use crate::install::parallel::Job;
 use crate::integrate::{self, Platform};
-use crate::manifest::bottle::BottleManifest;
+use crate::manifest::bottle::{BottleManifest, BrewPolicy};
+use crate::manifest::history::Operation;
 use crate::manifest::state::{BottleState, ToolState};
-use crate::ui;
-use chrono::Utc;
+use crate::ui::{self, OutputFormat};
+use crate::version;
 use console::style;
+use serde::Serialize;
 use std::collections::HashMap;
 
 /// Update to the latest bottle snapshot
-pub fn run(yes: bool) -> Result<()> {
-    // 1. Check if a bottle is installed and managed
-    let state = BottleState::load().ok_or(BottleError::NoBottleInstalled)?;
+pub fn run(yes: bool, dry_run: bool, strict: bool, format: OutputFormat) -> Result<()> {
+    require_dry_run_for_json(format, dry_run)?;
+
+    // 1. Check if a bottle is installed and managed (inside a project, the project's)
+    let project = load_project()?;
+    let state = match &project {
+        Some((_, manifest)) => BottleState::load_for(&manifest.name),
+        None => BottleState::load(),
+    }
+    .ok_or(BottleError::NoBottleInstalled)?;
 
     if !state.is_managed() {
         return Err(BottleError::AlreadyEjected);
     }
 
-    // 2. Fetch the latest manifest
-    let spinner = ui::spinner("Checking for updates...");
-    let latest = fetch_bottle_manifest(&state.bottle)?;
-    spinner.finish_and_clear();
+    // 2. Fetch the latest manifest (a project's is its bottle.json as it is now). A
+    // shared bottle is fetched again from where it was installed, and saved only
+    // once the update has been applied.
+    let (latest, shared) = match &project {
+        Some((_, manifest)) => (manifest.clone(), None),
+        None => {
+            let spinner = ui::spinner("Checking for updates...");
+            let latest = match &state.source {
+                Some(source) => refresh_shared_bottle(&state.bottle, source),
+                None => fetch_or_load_manifest(&state.bottle, None).map(|m| (m, None)),
+            };
+            spinner.finish_and_clear();
+            latest?
+        }
+    };
+
+    // Updating is what moves "latest" forward: look up the newest releases, ignoring
+    // the lock file, which is rewritten below
+    let latest = resolve_versions(&latest, None);
+    let lock_path = lock_path(
+        &state.bottle,
+        project.as_ref().map(|(p, _)| p.manifest_path()).as_deref(),
+    );
+
+    // The new snapshot's prerequisites must be met before anything changes
+    check_prerequisites(&latest)?;
 
     // 3. Calculate what needs updating (check tool versions, not just manifest version)
     let changes = calculate_changes(&state, &latest);
+    let plan = UpdatePlan {
+        bottle: &state.bottle,
+        from_version: &state.bottle_version,
+        to_version: &latest.version,
+        changes: &changes,
+    };
+
+    if format.is_json() {
+        return ui::print_json("update", &plan);
+    }
+
+    if dry_run {
+        if changes.is_empty() && latest.version == state.bottle_version {
+            println!(
+                "{} {} is already at the latest version ({})",
+                style("Bottle").bold(),
+                style(&state.bottle).cyan(),
+                state.bottle_version
+            );
+        } else {
+            println!();
+            println!("{}", style("[DRY RUN]").yellow().bold());
+            show_update_plan(&plan);
+            println!("{}", style("No changes made.").dim());
+            println!();
+        }
+        return Ok(());
+    }
 
     // 4. If no changes and same version, still update integrations then exit
     if changes.is_empty() && latest.version == state.bottle_version {
         // Update integrations even when tools are current (plugins/skills may have changed)
+        if let Some(shared) = &shared {
+            shared.save()?;
+        }
         update_integrations(&state, &latest)?;
+        if let Some(path) = &lock_path {
+            write_lock(path, &state)?;
+        }
 
         println!(
             "{} {} is already at the latest version ({})",
@@ -52,7 +121,7 @@ pub fn run(yes: bool) -> Result<()> {
         );
     } else {
         // Show update plan
-        show_update_plan(&state, &latest, &changes);
+        show_update_plan(&plan);
     }
 
     // 5. Confirm (unless -y)
@@ -62,45 +131,63 @@ pub fn run(yes: bool) -> Result<()> {
 
     println!();
 
-    // 6. Apply updates
-    let updated_tools = if changes.is_empty() {
-        state.tools.clone()
-    } else {
-        apply_updates(&state, &changes)?
-    };
+    // 6-9. Apply updates and save state (all-or-nothing with --strict)
+    // AIDEV-NOTE: Integrations aren't journaled by the transaction, so they are updated
+    // only after every step that can fail (and roll the update back) has succeeded.
+    let tx = strict.then(|| Transaction::begin(&state.bottle, Some(&state)));
+    run_strict(tx, |tx| {
+        // 6. Apply updates, then save a shared bottle's new manifest
+        let updated_tools = if changes.is_empty() {
+            state.tools.clone()
+        } else {
+            apply_updates(&state, &changes, latest.brew_policy, tx)?
+        };
+        if let Some(shared) = &shared {
+            shared.save()?;
+        }
 
-    // 7. Update platform integrations (plugins/skills)
-    update_integrations(&state, &latest)?;
+        // 7. Re-build snippet from latest manifest (may have changed)
+        let snippet = match build_agents_md_snippet(&latest) {
+            Ok(s) => s,
+            Err(e) => {
+                ui::print_warning(&format!("Failed to build AGENTS.md snippet: {}", e));
+                None
+            }
+        };
 
-    // 8. Re-build snippet from latest manifest (may have changed)
-    let snippet = match build_agents_md_snippet(&latest) {
-        Ok(s) => s,
-        Err(e) => {
-            ui::print_warning(&format!("Failed to build AGENTS.md snippet: {}", e));
-            None
+        // 8. Save updated state (preserve integrations and custom tools across updates)
+        let new_state = BottleState {
+            bottle: state.bottle.clone(),
+            bottle_version: latest.version.clone(),
+            installed_at: state.installed_at,
+            tools: updated_tools,
+            mode: state.mode.clone(),
+            integrations: state.integrations.clone(),
+            custom_tools: state.custom_tools.clone(),
+            source: state.source.clone(),
+        };
+        new_state
+            .save()
+            .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+
+        // Save snippet alongside state if present
+        if let Some(snippet_content) = &snippet {
+            new_state.save_snippet(snippet_content).map_err(|e| {
+                BottleError::Other(format!("Failed to save AGENTS.md snippet: {}", e))
+            })?;
         }
-    };
 
-    // 9. Save updated state (preserve integrations and custom tools across updates)
-    let new_state = BottleState {
-        bottle: state.bottle.clone(),
-        bottle_version: latest.version.clone(),
-        installed_at: state.installed_at,
-        tools: updated_tools,
-        mode: state.mode.clone(),
-        integrations: state.integrations.clone(),
-        custom_tools: state.custom_tools.clone(),
-    };
-    new_state
-        .save()
-        .map_err(|e| BottleError::Other(format!("Failed to save state: {}", e)))?;
+        if let Some(path) = &lock_path {
+            write_lock(path, &new_state)?;
+        }
 
-    // Save snippet alongside state if present
-    if let Some(snippet_content) = &snippet {
-        new_state
-            .save_snippet(snippet_content)
-            .map_err(|e| BottleError::Other(format!("Failed to save AGENTS.md snippet: {}", e)))?;
-    }
+        // 9. Update platform integrations (plugins/skills)
+        update_integrations(&state, &latest)?;
+
+        record_history(Operation::Update, &new_state);
+
+        Ok(())
+    })?;
 
     // 10. Show success
     println!();
@@ -110,8 +197,9 @@ pub fn run(yes: bool) -> Result<()> {
 }
 
 /// Types of changes that can occur during an update
-#[derive(Debug)]
-enum ToolChange {
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub(super) enum ToolChange {
     /// Tool version changed
     Upgrade { from: String, to: String },
     /// New tool added
@@ -120,64 +208,92 @@ enum ToolChange {
     Remove,
 }
 
-/// Calculate what tools need to change
-fn calculate_changes(state: &BottleState, latest: &BottleManifest) -> HashMap<String, ToolChange> {
-    let mut changes = HashMap::new();
+/// A change to a single tool
+#[derive(Debug, Serialize)]
+pub(super) struct PlannedChange {
+    pub tool: String,
+    #[serde(flatten)]
+    pub change: ToolChange,
+}
+
+/// Full update plan, shared by the human and JSON renderers
+#[derive(Debug, Serialize)]
+struct UpdatePlan<'a> {
+    bottle: &'a str,
+    from_version: &'a str,
+    to_version: &'a str,
+    changes: &'a [PlannedChange],
+}
+
+/// Calculate what tools need to change, sorted by tool name
+pub(super) fn calculate_changes(
+    state: &BottleState,
+    latest: &BottleManifest,
+) -> Vec<PlannedChange> {
+    let mut changes = Vec::new();
 
     // Check for upgrades and additions
     for (tool, new_version) in &latest.tools {
         if let Some(tool_state) = state.tools.get(tool) {
-            if &tool_state.version != new_version {
-                changes.insert(
-                    tool.clone(),
-                    ToolChange::Upgrade {
+            // "latest" or a range that could not be resolved (see resolve_versions)
+            // keeps what is installed if that satisfies it
+            if &tool_state.version != new_version
+                && !version::satisfies(new_version, &tool_state.version)
+            {
+                changes.push(PlannedChange {
+                    tool: tool.clone(),
+                    change: ToolChange::Upgrade {
                         from: tool_state.version.clone(),
                         to: new_version.clone(),
                     },
-                );
+                });
             }
         } else {
-            changes.insert(
-                tool.clone(),
-                ToolChange::Add {
+            changes.push(PlannedChange {
+                tool: tool.clone(),
+                change: ToolChange::Add {
                     version: new_version.clone(),
                 },
-            );
+            });
         }
     }
 
     // Check for removals
     for tool in state.tools.keys() {
         if !latest.tools.contains_key(tool) {
-            changes.insert(tool.clone(), ToolChange::Remove);
+            changes.push(PlannedChange {
+                tool: tool.clone(),
+                change: ToolChange::Remove,
+            });
         }
     }
 
+    changes.sort_by(|a, b| a.tool.cmp(&b.tool));
     changes
 }
 
 /// Display the update plan
-fn show_update_plan(
-    state: &BottleState,
-    latest: &BottleManifest,
-    changes: &HashMap<String, ToolChange>,
-) {
+fn show_update_plan(plan: &UpdatePlan) {
     println!();
     println!(
         "{}: {} {}",
         style("Update available").bold(),
-        style(&state.bottle).cyan(),
-        style(format!("{} -> {}", state.bottle_version, latest.version)).dim(),
+        style(plan.bottle).cyan(),
+        style(format!("{} -> {}", plan.from_version, plan.to_version)).dim(),
     );
     println!();
 
-    println!("{}:", style("Changes").bold());
+    if plan.changes.is_empty() {
+        println!("{}", style("No tool changes (metadata only).").dim());
+        println!();
+        return;
+    }
 
-    let mut sorted_changes: Vec<_> = changes.iter().collect();
-    sorted_changes.sort_by_key(|(name, _)| *name);
+    println!("{}:", style("Changes").bold());
 
-    for (tool, change) in sorted_changes {
-        match change {
+    for planned in plan.changes {
+        let tool = &planned.tool;
+        match &planned.change {
             ToolChange::Upgrade { from, to } => {
                 println!(
                     "  {:<12} {} {} {}",
@@ -210,82 +326,32 @@ fn show_update_plan(
 /// This matches the install.rs design - partial success is still success.
 fn apply_updates(
     state: &BottleState,
-    changes: &HashMap<String, ToolChange>,
+    changes: &[PlannedChange],
+    brew_policy: BrewPolicy,
+    tx: Option<&mut Transaction>,
 ) -> Result<HashMap<String, ToolState>> {
     let mut tools = state.tools.clone();
     let mut failures: Vec<(String, BottleError)> = Vec::new();
 
     println!("{}:", style("Updating tools").bold());
 
-    let mut sorted_changes: Vec<_> = changes.iter().collect();
-    sorted_changes.sort_by_key(|(name, _)| *name);
-
-    for (tool_name, change) in sorted_changes {
+    let mut jobs = Vec::new();
+    for PlannedChange {
+        tool: tool_name,
+        change,
+    } in changes
+    {
         match change {
-            ToolChange::Upgrade { to, .. } => {
-                print!("  {:<12} {} ", tool_name, style(to).dim());
-
-                // Fetch tool definition
-                let tool_def = match fetch_tool_definition(tool_name) {
-                    Ok(def) => def,
-                    Err(e) => {
-                        println!("{}", style("failed").red());
-                        failures.push((tool_name.clone(), e));
-                        continue;
-                    }
-                };
-
-                // Upgrade the tool
-                match install::install_tool(&tool_def, to) {
-                    Ok(method) => {
-                        println!("{}", style("updated").green());
-                        tools.insert(
-                            tool_name.clone(),
-                            ToolState {
-                                version: to.clone(),
-                                installed_at: Utc::now(),
-                                method,
-                            },
-                        );
-                    }
-                    Err(e) => {
-                        println!("{}", style("failed").red());
-                        failures.push((tool_name.clone(), e));
-                    }
-                }
-            }
-            ToolChange::Add { version } => {
-                print!("  {:<12} {} ", tool_name, style(version).dim());
-
-                // Fetch tool definition
-                let tool_def = match fetch_tool_definition(tool_name) {
-                    Ok(def) => def,
-                    Err(e) => {
-                        println!("{}", style("failed").red());
-                        failures.push((tool_name.clone(), e));
-                        continue;
-                    }
-                };
-
-                // Install the new tool
-                match install::install_tool(&tool_def, version) {
-                    Ok(method) => {
-                        println!("{}", style("installed").green());
-                        tools.insert(
-                            tool_name.clone(),
-                            ToolState {
-                                version: version.clone(),
-                                installed_at: Utc::now(),
-                                method,
-                            },
-                        );
-                    }
-                    Err(e) => {
-                        println!("{}", style("failed").red());
-                        failures.push((tool_name.clone(), e));
-                    }
-                }
-            }
+            ToolChange::Upgrade { to, .. } => jobs.push(Job {
+                tool: tool_name.clone(),
+                version: to.clone(),
+                done: "updated",
+            }),
+            ToolChange::Add { version } => jobs.push(Job {
+                tool: tool_name.clone(),
+                version: version.clone(),
+                done: "installed",
+            }),
             ToolChange::Remove => {
                 // Just remove from state - don't uninstall the binary
                 // User may want to keep using it manually
@@ -296,6 +362,9 @@ fn apply_updates(
         }
     }
 
+    // Upgraded and new tools install concurrently; a failed upgrade keeps its old state
+    install_tool_jobs(&jobs, brew_policy, &mut tools, &mut failures, tx)?;
+
     println!();
 
     if !failures.is_empty() {
@@ -313,7 +382,7 @@ fn apply_updates(
         state
             .tools
             .keys()
-            .all(|tool| changes.contains_key(tool) || tools.contains_key(tool)),
+            .all(|tool| changes.iter().any(|c| &c.tool == tool) || tools.contains_key(tool)),
         "Unchanged tools were unexpectedly removed from state"
     );
 
@@ -1,59 +1,306 @@
 use super::common::get_local_manifest_path;
+use super::diff::load_tool_definition;
 use crate::error::{BottleError, Result};
+use crate::fetch::fetch_latest_version;
+use crate::ui;
+use crate::version;
 use console::style;
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::fs;
 
-/// Bump a tool version in a bottle manifest (curator command)
-pub fn run(bottle: &str, tool: &str, version: &str) -> Result<()> {
-    // Find the manifest
-    let manifest_path = get_local_manifest_path(bottle)?;
+/// Manifest sections with version pins, in the order names are looked up
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Tool,
+    CustomTool,
+    OpencodePlugin,
+    McpServer,
+}
+
+impl Section {
+    fn label(self) -> &'static str {
+        match self {
+            Section::Tool => "tool",
+            Section::CustomTool => "custom tool",
+            Section::OpencodePlugin => "OpenCode plugin",
+            Section::McpServer => "MCP server",
+        }
+    }
+}
 
-    // Read and parse
+/// One pinned version in a manifest
+#[derive(Debug)]
+struct Pin {
+    section: Section,
+    name: String,
+    version: String,
+    /// MCP servers pin an npm package in their args: (arg index, package)
+    mcp_package: Option<(usize, String)>,
+}
+
+/// Bump pinned versions in a bottle manifest (curator command).
+/// `version` may be "latest" to look up the newest published release; a name pinned
+/// in several sections is bumped in each. `all` bumps every exact pin that is behind
+/// its registry.
+pub fn run(bottle: &str, name: Option<&str>, version: &str, all: bool) -> Result<()> {
+    // 1. Find the manifest and its pins
+    let manifest_path = get_local_manifest_path(bottle)?;
     let contents = fs::read_to_string(&manifest_path)?;
     let mut manifest: Value = serde_json::from_str(&contents)?;
+    let pins = collect_pins(&manifest);
 
-    // Get tools object
-    let tools = manifest
-        .get_mut("tools")
-        .and_then(|t| t.as_object_mut())
-        .ok_or_else(|| BottleError::Other("Manifest has no tools section".to_string()))?;
-
-    // Check tool exists and get old version
-    let old_version = tools
-        .get(tool)
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-        .ok_or_else(|| BottleError::ToolNotFound(tool.to_string()))?;
+    // 2. Work out the new versions
+    let mut lookups_failed = false;
+    let changes = if all {
+        let (stale, failed) = stale_pins(&manifest, pins);
+        lookups_failed = failed;
+        stale
+    } else {
+        let name =
+            name.ok_or_else(|| BottleError::Other("Name a pin or pass --all".to_string()))?;
+        // A name pinned in several sections (a tool and its MCP server) bumps them all
+        let matching: Vec<Pin> = pins.into_iter().filter(|p| p.name == name).collect();
+        if matching.is_empty() {
+            return Err(BottleError::ToolNotFound(name.to_string()));
+        }
+        let mut changes = Vec::new();
+        for pin in matching {
+            let new_version = if version == "latest" {
+                latest_version(&manifest, &pin)?
+            } else {
+                version.to_string()
+            };
+            if new_version != pin.version {
+                changes.push((pin, new_version));
+            }
+        }
+        if changes.is_empty() {
+            let target = match version {
+                "latest" => "its latest published version",
+                version => version,
+            };
+            ui::print_success(&format!(
+                "{} is already at {} in {} bottle",
+                name, target, bottle
+            ));
+            return Ok(());
+        }
+        changes
+    };
 
-    // Update version
-    tools.insert(tool.to_string(), Value::String(version.to_string()));
+    if changes.is_empty() {
+        // Failed lookups mean we can't claim everything is current
+        if !lookups_failed {
+            ui::print_success(&format!(
+                "All pins in {} bottle are at their latest published versions.",
+                bottle
+            ));
+        }
+        return Ok(());
+    }
 
-    // Write back with pretty formatting
+    // 3. Write them back with pretty formatting
+    for (pin, new_version) in &changes {
+        set_pin(&mut manifest, pin, new_version);
+    }
     let updated = serde_json::to_string_pretty(&manifest)?;
     fs::write(&manifest_path, updated + "\n")?;
 
-    // Report
+    // 4. Report
     println!();
     println!(
-        "{} Updated {} in {} bottle:",
+        "{} Updated {} bottle:",
         style("✓").green(),
-        style(tool).cyan(),
         style(bottle).cyan()
     );
-    println!(
-        "  {} → {}",
-        style(&old_version).dim(),
-        style(version).green()
-    );
-    println!();
+    for (pin, new_version) in &changes {
+        println!(
+            "  {:<14} {:<16} {} → {}",
+            pin.name,
+            style(pin.section.label()).dim(),
+            style(&pin.version).dim(),
+            style(new_version).green()
+        );
+    }
+
+    // 5. Catch mistakes before they're released
+    super::validate::run(bottle)?;
+
     println!("{}:", style("Next steps").bold());
     println!(
-        "  {} - Verify manifest is valid",
-        style("bottle validate").cyan()
+        "  {} - Tag and publish",
+        style(format!("bottle release {}", bottle)).cyan()
     );
-    println!("  {} - Tag and publish", style("bottle release").cyan());
     println!();
 
     Ok(())
 }
+
+/// The newest published release of a pin's package
+fn latest_version(manifest: &Value, pin: &Pin) -> Result<String> {
+    let spinner = ui::spinner(&format!(
+        "Looking up the latest {} ({})...",
+        pin.name,
+        pin.section.label()
+    ));
+    let newest = registry_package(manifest, pin).and_then(|source| {
+        let (registry, package) = source.ok_or_else(|| {
+            BottleError::Other(format!(
+                "Custom tool '{}' has no cargo or npm package to look up; give a version",
+                pin.name
+            ))
+        })?;
+        fetch_latest_version(&registry, &package)
+    });
+    spinner.finish_and_clear();
+    newest
+}
+
+/// Every pin in the manifest, in lookup order
+fn collect_pins(manifest: &Value) -> Vec<Pin> {
+    let mut pins = Vec::new();
+    let entries = |section: &str| {
+        let mut entries: Vec<(String, Value)> = manifest
+            .get(section)
+            .and_then(|s| s.as_object())
+            .map(|s| s.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    };
+    let pin = |section, name: &str, version: &str| Pin {
+        section,
+        name: name.to_string(),
+        version: version.to_string(),
+        mcp_package: None,
+    };
+
+    for (name, version) in entries("tools") {
+        if let Some(version) = version.as_str() {
+            pins.push(pin(Section::Tool, &name, version));
+        }
+    }
+    for (name, tool) in entries("custom_tools") {
+        if let Some(version) = tool.get("version").and_then(|v| v.as_str()) {
+            pins.push(pin(Section::CustomTool, &name, version));
+        }
+    }
+    for (name, version) in entries("opencode_plugins") {
+        if let Some(version) = version.as_str() {
+            pins.push(pin(Section::OpencodePlugin, &name, version));
+        }
+    }
+    for (name, server) in entries("mcp_servers") {
+        if let Some((index, package, version)) = mcp_package(&server) {
+            pins.push(Pin {
+                mcp_package: Some((index, package)),
+                ..pin(Section::McpServer, &name, &version)
+            });
+        }
+    }
+
+    pins
+}
+
+/// The versioned npm package an `npx` MCP server runs (`@scope/pkg@1.2.3`)
+fn mcp_package(server: &Value) -> Option<(usize, String, String)> {
+    let command = server.get("command")?.as_str()?;
+    if command != "npx" {
+        return None;
+    }
+    server
+        .get("args")?
+        .as_array()?
+        .iter()
+        .enumerate()
+        .find_map(|(index, arg)| {
+            // rsplit so a scope's leading '@' stays with the package
+            let (package, version) = arg.as_str()?.rsplit_once('@')?;
+            if package.is_empty() || version::Spec::parse(version).is_none() {
+                return None;
+            }
+            Some((index, package.to_string(), version.to_string()))
+        })
+}
+
+/// The registry and package a pin's releases are published as. None for custom
+/// tools installed only through brew or a binary URL.
+fn registry_package(manifest: &Value, pin: &Pin) -> Result<Option<(String, String)>> {
+    let source = match pin.section {
+        Section::Tool => {
+            let def = load_tool_definition(&pin.name)?;
+            (def.registry, def.package)
+        }
+        Section::OpencodePlugin => ("npm".to_string(), pin.name.clone()),
+        Section::McpServer => {
+            let (_, package) = pin.mcp_package.as_ref().expect("MCP pins have a package");
+            ("npm".to_string(), package.clone())
+        }
+        Section::CustomTool => {
+            let install = &manifest["custom_tools"][&pin.name]["install"];
+            if let Some(krate) = install.get("cargo").and_then(|c| c.as_str()) {
+                ("crates.io".to_string(), krate.to_string())
+            } else if let Some(package) = install.get("npm").and_then(|n| n.as_str()) {
+                ("npm".to_string(), package.to_string())
+            } else {
+                return Ok(None);
+            }
+        }
+    };
+    Ok(Some(source))
+}
+
+/// Exact pins behind their newest published release, with that release.
+/// `latest` and ranges are left alone: installs already resolve them. The flag is
+/// set when a lookup failed.
+fn stale_pins(manifest: &Value, pins: Vec<Pin>) -> (Vec<(Pin, String)>, bool) {
+    let spinner = ui::spinner("Looking up latest versions...");
+    let mut stale = Vec::new();
+    let mut warnings = Vec::new();
+
+    for pin in pins {
+        if !version::is_exact(&pin.version) {
+            continue;
+        }
+        let newest = match registry_package(manifest, &pin) {
+            Ok(Some((registry, package))) => fetch_latest_version(&registry, &package),
+            Ok(None) => continue,
+            Err(e) => Err(e),
+        };
+        match newest {
+            Ok(newest) => {
+                if version::compare(&pin.version, &newest) == Some(Ordering::Less) {
+                    stale.push((pin, newest));
+                }
+            }
+            Err(e) => warnings.push(format!(
+                "Could not look up the latest {} ({}): {}",
+                pin.name,
+                pin.section.label(),
+                e
+            )),
+        }
+    }
+
+    spinner.finish_and_clear();
+    for warning in &warnings {
+        ui::print_warning(warning);
+    }
+    (stale, !warnings.is_empty())
+}
+
+/// Write a new version into the manifest
+fn set_pin(manifest: &mut Value, pin: &Pin, version: &str) {
+    let new = Value::String(version.to_string());
+    match pin.section {
+        Section::Tool => manifest["tools"][&pin.name] = new,
+        Section::OpencodePlugin => manifest["opencode_plugins"][&pin.name] = new,
+        Section::CustomTool => manifest["custom_tools"][&pin.name]["version"] = new,
+        Section::McpServer => {
+            let (index, package) = pin.mcp_package.as_ref().expect("MCP pins have a package");
+            manifest["mcp_servers"][&pin.name]["args"][*index] =
+                Value::String(format!("{}@{}", package, version));
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use super::common::get_local_manifest_path;
 use crate::error::{BottleError, Result};
+use crate::manifest::tool::{Prebuilt, Requirement, ToolDefinition};
+use crate::version::Spec;
 use console::style;
 use serde_json::Value;
 use std::collections::HashSet;
@@ -22,8 +24,9 @@ pub fn run(bottle: &str) -> Result<()> {
     // 1. Schema validation
     check_schema(&manifest, &mut errors);
 
-    // 2. Tool definitions exist
-    check_tool_definitions(&manifest, &mut errors);
+    // 2. Tool definitions exist (with pinned prebuilt binaries and valid requires)
+    check_tool_definitions(&manifest, &mut errors, &mut warnings);
+    check_prerequisites(&manifest, &mut errors);
 
     // 3. Version format
     check_version_format(&manifest, &mut warnings);
@@ -101,23 +104,104 @@ fn check_schema(manifest: &Value, errors: &mut Vec<String>) {
 }
 
 /// Check that each tool has a definition file
-fn check_tool_definitions(manifest: &Value, errors: &mut Vec<String>) {
+fn check_tool_definitions(manifest: &Value, errors: &mut Vec<String>, warnings: &mut Vec<String>) {
     let Some(tools) = manifest.get("tools").and_then(|t| t.as_object()) else {
         return;
     };
 
-    for tool_name in tools.keys() {
+    for (tool_name, version) in tools {
         let def_path = PathBuf::from(format!("tools/{}.json", tool_name));
         if !def_path.exists() {
             errors.push(format!(
                 "Tool '{}' has no definition at tools/{}.json",
                 tool_name, tool_name
             ));
+            continue;
+        }
+
+        let def = fs::read_to_string(&def_path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<ToolDefinition>(&c).ok());
+        let Some(def) = def else {
+            errors.push(format!(
+                "Tool '{}' definition at tools/{}.json could not be parsed",
+                tool_name, tool_name
+            ));
+            continue;
+        };
+
+        if let (Some(prebuilt), Some(version)) = (&def.prebuilt, version.as_str()) {
+            check_prebuilt(tool_name, version, prebuilt, errors, warnings);
+        }
+
+        for spec in &def.requires {
+            if Requirement::parse(spec).is_none() {
+                errors.push(format!(
+                    "Tool '{}' has a malformed requires entry '{}' (expected 'name' or 'name>=version')",
+                    tool_name, spec
+                ));
+            }
+        }
+    }
+}
+
+/// Check prerequisite keys are binaries, optionally with a minimum version
+fn check_prerequisites(manifest: &Value, errors: &mut Vec<String>) {
+    let Some(prerequisites) = manifest.get("prerequisites").and_then(|p| p.as_object()) else {
+        return;
+    };
+
+    for key in prerequisites.keys() {
+        if Requirement::parse(key).is_none() {
+            errors.push(format!(
+                "Prerequisite '{}' is malformed (expected 'name' or 'name>=version')",
+                key
+            ));
         }
     }
 }
 
-/// Check version formats look like semver
+/// Check a tool's prebuilt digests for the version this bottle pins
+fn check_prebuilt(
+    tool_name: &str,
+    version: &str,
+    prebuilt: &Prebuilt,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    if !prebuilt.url.starts_with("https://") {
+        errors.push(format!(
+            "Tool '{}' prebuilt url must use HTTPS: {}",
+            tool_name, prebuilt.url
+        ));
+    }
+
+    let Some(digests) = prebuilt.sha256.get(version) else {
+        warnings.push(format!(
+            "Tool '{}' has prebuilt binaries but no sha256 digests for {}; it will build from source",
+            tool_name, version
+        ));
+        return;
+    };
+
+    for target in prebuilt.assets.keys() {
+        match digests.get(target) {
+            None => errors.push(format!(
+                "Tool '{}' {} has no sha256 for target '{}'",
+                tool_name, version, target
+            )),
+            Some(d) if !(d.len() == 64 && d.chars().all(|c| c.is_ascii_hexdigit())) => {
+                errors.push(format!(
+                    "Tool '{}' {} sha256 for '{}' must be a 64-character hex digest",
+                    tool_name, version, target
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Check versions are exact, a semver range, or "latest"
 fn check_version_format(manifest: &Value, warnings: &mut Vec<String>) {
     let Some(tools) = manifest.get("tools").and_then(|t| t.as_object()) else {
         return;
@@ -125,9 +209,9 @@ fn check_version_format(manifest: &Value, warnings: &mut Vec<String>) {
 
     for (tool_name, version) in tools {
         if let Some(v) = version.as_str() {
-            if !looks_like_semver(v) {
+            if Spec::parse(v).is_none() {
                 warnings.push(format!(
-                    "Tool '{}' version '{}' doesn't look like semver (x.y.z)",
+                    "Tool '{}' version '{}' isn't a version (x.y.z), semver range or 'latest'",
                     tool_name, v
                 ));
             }
@@ -135,15 +219,6 @@ fn check_version_format(manifest: &Value, warnings: &mut Vec<String>) {
     }
 }
 
-/// Simple semver check - must have at least x.y format
-fn looks_like_semver(v: &str) -> bool {
-    let parts: Vec<&str> = v.split('.').collect();
-    if parts.len() < 2 {
-        return false;
-    }
-    parts.iter().all(|p| p.parse::<u32>().is_ok())
-}
-
 /// Check for duplicate plugin entries
 fn check_duplicate_plugins(manifest: &Value, errors: &mut Vec<String>) {
     if let Some(plugins) = manifest.get("plugins").and_then(|p| p.as_array()) {
@@ -295,6 +370,45 @@ fn check_custom_tools(manifest: &Value, errors: &mut Vec<String>, warnings: &mut
             ));
         }
 
+        // binary_url downloads should be pinned, and archives need to say where the binary is
+        if let Some(url) = install.get("binary_url").and_then(|u| u.as_str()) {
+            match install.get("sha256") {
+                None => warnings.push(format!(
+                    "Custom tool '{}' binary_url has no sha256 digests; downloads are not verified",
+                    name
+                )),
+                Some(digests) => match digests.as_object() {
+                    Some(digests) => {
+                        for (platform, digest) in digests {
+                            let valid = digest.as_str().is_some_and(|d| {
+                                d.len() == 64 && d.chars().all(|c| c.is_ascii_hexdigit())
+                            });
+                            if !valid {
+                                errors.push(format!(
+                                    "Custom tool '{}' sha256 for '{}' must be a 64-character hex digest",
+                                    name, platform
+                                ));
+                            }
+                        }
+                    }
+                    None => errors.push(format!(
+                        "Custom tool '{}' 'sha256' must be an object of platform -> digest",
+                        name
+                    )),
+                },
+            }
+
+            let path = url.split(['?', '#']).next().unwrap_or(url);
+            let is_archive =
+                path.ends_with(".tar.gz") || path.ends_with(".tgz") || path.ends_with(".zip");
+            if is_archive && install.get("binary_path").is_none() {
+                errors.push(format!(
+                    "Custom tool '{}' binary_url is an archive but has no binary_path",
+                    name
+                ));
+            }
+        }
+
         // version is required
         if tool.get("version").and_then(|v| v.as_str()).is_none() {
             errors.push(format!(
@@ -302,9 +416,9 @@ fn check_custom_tools(manifest: &Value, errors: &mut Vec<String>, warnings: &mut
                 name
             ));
         } else if let Some(v) = tool.get("version").and_then(|v| v.as_str()) {
-            if !looks_like_semver(v) && v != "latest" {
+            if Spec::parse(v).is_none() {
                 warnings.push(format!(
-                    "Custom tool '{}' version '{}' doesn't look like semver",
+                    "Custom tool '{}' version '{}' isn't a version, semver range or 'latest'",
                     name, v
                 ));
             }
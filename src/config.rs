@@ -0,0 +1,46 @@
+//! User configuration (~/.bottle/config.toml)
+
+use crate::error::{BottleError, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Settings read from ~/.bottle/config.toml. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Manifest registry (https URL, file:// directory, or git+<url>[#ref])
+    #[serde(default)]
+    pub registry: Option<String>,
+    /// Extra minisign public keys trusted for registry and bespoke signatures
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+    /// Bespoke bottles explicitly trusted without a signature
+    #[serde(default)]
+    pub unsigned_bottles: Vec<String>,
+    /// Maximum number of tools installed at once
+    #[serde(default)]
+    pub install_jobs: Option<usize>,
+}
+
+impl Config {
+    /// Get the path to the config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::home_dir().map(|h| h.join(".bottle").join("config.toml"))
+    }
+
+    /// Load the config file, returning defaults if it does not exist
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        toml::from_str(&contents).map_err(|e| {
+            BottleError::Other(format!("Invalid config file {}: {}", path.display(), e))
+        })
+    }
+}
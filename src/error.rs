@@ -42,6 +42,15 @@ pub enum BottleError {
     #[error("Validation failed: {0}")]
     ValidationError(String),
 
+    #[error("Signature verification failed: {0}")]
+    SignatureError(String),
+
+    #[error("{0} is not cached. Run once without --offline to populate ~/.bottle/cache.")]
+    NotCached(String),
+
+    #[error("Operation failed and was rolled back: {0}")]
+    RolledBack(String),
+
     #[error("{0}")]
     Other(String),
 }
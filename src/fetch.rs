@@ -1,9 +1,25 @@
+use crate::cache;
 use crate::error::{BottleError, Result};
 use crate::manifest::bottle::BottleManifest;
+use crate::manifest::index::BottleIndex;
 use crate::manifest::tool::ToolDefinition;
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::registry::{self, Registry};
+use crate::signature;
+use crate::ui;
+use once_cell::sync::Lazy;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-const GITHUB_RAW_BASE: &str = "https://raw.githubusercontent.com/open-horizon-labs/bottle/master";
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tool definitions already fetched by this run
+/// AIDEV-NOTE: check_prerequisites reads every definition before the install
+/// workers need them again; both take them from here instead of the registry.
+static DEFINITIONS: Lazy<Mutex<HashMap<String, ToolDefinition>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Get cache-busting query param
 fn cache_buster() -> u64 {
@@ -13,45 +29,296 @@ fn cache_buster() -> u64 {
         .unwrap_or(0)
 }
 
-/// Fetch a bottle manifest from GitHub
-pub fn fetch_bottle_manifest(bottle: &str) -> Result<BottleManifest> {
-    let url = format!(
-        "{}/bottles/{}/manifest.json?t={}",
-        GITHUB_RAW_BASE,
-        bottle,
-        cache_buster()
-    );
-    let response = reqwest::blocking::get(&url)?;
+/// Fetch a document (path relative to the registry root) from the configured registry.
+/// Returns None if the registry has no such document.
+pub fn fetch_registry_file(path: &str) -> Result<Option<String>> {
+    let registry = registry::current()?;
+    match registry {
+        Registry::Http(base) => {
+            let key = match registry.cache_prefix() {
+                Some(prefix) => format!("{}/{}", prefix, path),
+                None => path.to_string(),
+            };
+            fetch_http_document(base, path, &key)
+        }
+        local => local.read_local(path),
+    }
+}
 
-    if response.status() == 404 {
-        return Err(BottleError::BottleNotFound(bottle.to_string()));
+/// Fetch a document from an HTTP registry through the cache (`key` is the cache path).
+/// AIDEV-NOTE: Revalidates with ETag/Last-Modified when a cached copy exists, and falls
+/// back to the cached copy (with a staleness warning) if the registry is unreachable.
+fn fetch_http_document(base: &str, path: &str, key: &str) -> Result<Option<String>> {
+    let cached = cache::load(key);
+
+    if cache::is_offline() {
+        let doc = cached.ok_or_else(|| BottleError::NotCached(path.to_string()))?;
+        ui::eprint_warning(&format!(
+            "Using cached {} from {} (offline)",
+            path,
+            doc.age()
+        ));
+        return Ok(Some(doc.body));
+    }
+
+    let url = format!("{}/{}?t={}", base, path, cache_buster());
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REGISTRY_TIMEOUT)
+        .build()?;
+    let mut request = client.get(&url);
+    if let Some(doc) = &cached {
+        if let Some(etag) = &doc.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &doc.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = match request.send().and_then(|r| match r.status() {
+        StatusCode::NOT_FOUND | StatusCode::NOT_MODIFIED => Ok(r),
+        _ => r.error_for_status(),
+    }) {
+        Ok(response) => response,
+        Err(e) => {
+            let Some(doc) = cached else {
+                return Err(e.into());
+            };
+            ui::eprint_warning(&format!(
+                "Could not reach registry ({}), using cached {} from {}",
+                e,
+                path,
+                doc.age()
+            ));
+            return Ok(Some(doc.body));
+        }
+    };
+
+    match response.status() {
+        StatusCode::NOT_FOUND => Ok(None),
+        StatusCode::NOT_MODIFIED => {
+            let doc = cached.ok_or_else(|| {
+                BottleError::Other(format!("Registry returned 304 for uncached {}", path))
+            })?;
+            // Cache write failures only cost us a future revalidation
+            let _ = cache::touch(&doc, key);
+            Ok(Some(doc.body))
+        }
+        _ => {
+            let header = |name| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string())
+            };
+            let etag = header(ETAG);
+            let last_modified = header(LAST_MODIFIED);
+            let body = response.text()?;
+            let _ = cache::store(key, &body, etag, last_modified);
+            Ok(Some(body))
+        }
+    }
+}
+
+/// Fetch a registry document together with its detached signature and verify it
+/// AIDEV-NOTE: Signatures go through the same cache as the documents, so --offline
+/// verifies against the signature that was fetched alongside the cached body.
+fn fetch_signed_file(path: &str) -> Result<Option<String>> {
+    let Some(body) = fetch_registry_file(path)? else {
+        return Ok(None);
+    };
+    // An uncached signature offline is treated as missing; verification decides
+    let sig = match fetch_registry_file(&signature::signature_path(path)) {
+        Ok(sig) => sig,
+        Err(BottleError::NotCached(_)) => None,
+        Err(e) => return Err(e),
+    };
+    signature::verify_registry_document(path, &body, sig.as_deref())?;
+    Ok(Some(body))
+}
+
+/// Check a bottle or tool name before it becomes part of a registry path and cache
+/// key. Tool names come from third-party manifests, so `../x` must not reach either.
+fn check_name(kind: &str, name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(BottleError::ValidationError(format!(
+            "Invalid {} name '{}': use letters, digits, hyphens and underscores",
+            kind, name
+        )))
     }
+}
 
-    let manifest: BottleManifest = response.json()?;
+/// Fetch a bottle manifest from the registry (or the local cache)
+pub fn fetch_bottle_manifest(bottle: &str) -> Result<BottleManifest> {
+    check_name("bottle", bottle)?;
+    let path = format!("bottles/{}/manifest.json", bottle);
+    let body =
+        fetch_signed_file(&path)?.ok_or_else(|| BottleError::BottleNotFound(bottle.into()))?;
+    let manifest: BottleManifest = serde_json::from_str(&body)?;
     Ok(manifest)
 }
 
-/// Fetch a tool definition from GitHub
+/// Fetch a tool definition from the registry (or the local cache), once per run
 pub fn fetch_tool_definition(tool: &str) -> Result<ToolDefinition> {
-    let url = format!(
-        "{}/tools/{}.json?t={}",
-        GITHUB_RAW_BASE,
-        tool,
-        cache_buster()
-    );
-    let response = reqwest::blocking::get(&url)?;
-
-    if response.status() == 404 {
-        return Err(BottleError::ToolNotFound(tool.to_string()));
+    if let Some(definition) = DEFINITIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(tool)
+    {
+        return Ok(definition.clone());
     }
 
-    let definition: ToolDefinition = response.json()?;
+    check_name("tool", tool)?;
+    let path = format!("tools/{}.json", tool);
+    let body = fetch_signed_file(&path)?.ok_or_else(|| BottleError::ToolNotFound(tool.into()))?;
+    let definition: ToolDefinition = serde_json::from_str(&body)?;
+    DEFINITIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(tool.to_string(), definition.clone());
     Ok(definition)
 }
 
-/// List available curated bottles
-pub fn list_available_bottles() -> Result<Vec<String>> {
-    // For now, return hardcoded list
-    // In the future, could fetch from an index file
-    Ok(vec!["stable".to_string(), "edge".to_string()])
+/// Fetch the curated bottle index from the registry (or the local cache)
+pub fn fetch_bottle_index() -> Result<BottleIndex> {
+    let body = fetch_registry_file(BottleIndex::PATH)?
+        .ok_or_else(|| BottleError::Other(format!("Registry has no {}", BottleIndex::PATH)))?;
+    let index: BottleIndex = serde_json::from_str(&body)?;
+    Ok(index)
+}
+
+/// Fetch the newest published version of a package from its registry.
+/// `registry` is the `registry` field of a tool definition ("crates.io" or "npm").
+pub fn fetch_latest_version(registry: &str, package: &str) -> Result<String> {
+    match registry {
+        "crates.io" => {
+            let body = query_registry(registry, package, &crates_io_url(package))?;
+
+            // Prefer the newest stable release; fall back to newest of any kind
+            let krate = &body["crate"];
+            krate["max_stable_version"]
+                .as_str()
+                .or_else(|| krate["newest_version"].as_str())
+                .or_else(|| krate["max_version"].as_str())
+                .map(|v| v.to_string())
+                .ok_or_else(|| {
+                    BottleError::Other(format!("crates.io returned no version for {}", package))
+                })
+        }
+        "npm" => {
+            let url = format!("{}/latest", npm_url(package));
+            let body = query_registry(registry, package, &url)?;
+            body["version"]
+                .as_str()
+                .map(|v| v.to_string())
+                .ok_or_else(|| {
+                    BottleError::Other(format!("npm returned no version for {}", package))
+                })
+        }
+        other => Err(BottleError::Other(format!(
+            "Unsupported registry '{}' for {}",
+            other, package
+        ))),
+    }
+}
+
+/// Every published version of a package, for resolving semver ranges.
+/// Yanked crates.io releases are left out.
+pub fn fetch_versions(registry: &str, package: &str) -> Result<Vec<String>> {
+    match registry {
+        "crates.io" => {
+            let body = query_registry(registry, package, &crates_io_url(package))?;
+            Ok(body["versions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|v| !v["yanked"].as_bool().unwrap_or(false))
+                .filter_map(|v| v["num"].as_str().map(|n| n.to_string()))
+                .collect())
+        }
+        "npm" => {
+            let body = query_registry(registry, package, &npm_url(package))?;
+            Ok(body["versions"]
+                .as_object()
+                .map(|versions| versions.keys().cloned().collect())
+                .unwrap_or_default())
+        }
+        other => Err(BottleError::Other(format!(
+            "Unsupported registry '{}' for {}",
+            other, package
+        ))),
+    }
+}
+
+/// Fetch a document from a URL outside the registry (shared bespoke bottles).
+/// Returns None if the server has no such document. Nothing is cached: the copy
+/// that matters is the one saved under ~/.bottle/bottles.
+pub fn fetch_url(url: &str) -> Result<Option<String>> {
+    if cache::is_offline() {
+        return Err(BottleError::Other(format!(
+            "Cannot fetch {} in offline mode",
+            url
+        )));
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REGISTRY_TIMEOUT)
+        .build()?;
+    let response = client.get(url).send()?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.text()?))
+}
+
+fn crates_io_url(package: &str) -> String {
+    format!("https://crates.io/api/v1/crates/{}", package)
+}
+
+fn npm_url(package: &str) -> String {
+    // Scoped packages (@scope/name) need the slash encoded
+    format!("https://registry.npmjs.org/{}", package.replace('/', "%2F"))
+}
+
+/// GET a package registry's JSON document for a package
+fn query_registry(registry: &str, package: &str, url: &str) -> Result<serde_json::Value> {
+    if cache::is_offline() {
+        return Err(BottleError::Other(format!(
+            "Cannot query {} for {} in offline mode",
+            registry, package
+        )));
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REGISTRY_TIMEOUT)
+        // crates.io rejects requests without a User-Agent
+        .user_agent(concat!("bottle/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let response = client.get(url).send()?;
+    if response.status() == 404 {
+        return Err(BottleError::ToolNotFound(package.to_string()));
+    }
+    Ok(response.error_for_status()?.json()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_cannot_leave_their_directory() {
+        assert!(check_name("tool", "oh-mcp").is_ok());
+        assert!(check_name("bottle", "example_team").is_ok());
+        for name in ["", "../../x", "a/b", "..", "x\\y"] {
+            assert!(check_name("tool", name).is_err(), "{:?} accepted", name);
+        }
+    }
 }
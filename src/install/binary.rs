@@ -0,0 +1,275 @@
+//! Direct binary downloads for custom tools (`binary_url`)
+//!
+//! Downloads are checked against the per-platform `sha256` digests in the manifest and
+//! may be raw binaries or `.tar.gz`/`.zip` archives with the binary at `binary_path`.
+//! The download, verify and unpack steps are shared with prebuilt curated tools.
+
+use crate::error::{BottleError, Result};
+use crate::manifest::bottle::CustomToolInstall;
+use crate::ui;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Download format, decided by the URL's extension
+enum Archive {
+    Raw,
+    TarGz,
+    Zip,
+}
+
+impl Archive {
+    fn from_url(url: &str) -> Self {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Archive::TarGz
+        } else if path.ends_with(".zip") {
+            Archive::Zip
+        } else {
+            Archive::Raw
+        }
+    }
+}
+
+/// Current platform as `{os}-{arch}`, the key used for `sha256` digests
+pub fn platform() -> String {
+    expand_placeholders("{platform}")
+}
+
+/// Expand placeholders in a binary URL or archive path
+/// Supported: {arch}, {os}, {platform}, {arm64}
+pub fn expand_placeholders(template: &str) -> String {
+    let arch = std::env::consts::ARCH;
+    let os = std::env::consts::OS;
+
+    // Alternative arch name for Apple Silicon (many releases use "arm64" instead of "aarch64")
+    let arm64_name = match arch {
+        "aarch64" => "arm64",
+        _ => arch,
+    };
+
+    let os_name = match os {
+        "macos" => "darwin",
+        "linux" => "linux",
+        "windows" => "windows",
+        _ => os,
+    };
+
+    template
+        .replace("{arch}", arch)
+        .replace("{arm64}", arm64_name)
+        .replace("{os}", os_name)
+        .replace("{platform}", &format!("{}-{}", os_name, arch))
+}
+
+/// Install a custom tool from its `binary_url` into ~/.local/bin
+pub fn install(name: &str, install: &CustomToolInstall) -> Result<()> {
+    let template = install
+        .binary_url
+        .as_deref()
+        .ok_or_else(|| install_error(name, "No binary_url in manifest".to_string()))?;
+    let url = expand_placeholders(template);
+
+    // 1. Download (HTTPS only)
+    let bytes = download(name, &url)?;
+
+    // 2. Verify the checksum before anything touches the disk
+    verify_checksum(name, &url, &bytes, &install.sha256, &platform())?;
+
+    // 3. Unpack the binary
+    let binary = unpack(name, &url, bytes, install.binary_path.as_deref())?;
+
+    // 4. Write to ~/.local/bin
+    write_binary(name, &binary)
+}
+
+/// Take the binary out of a download: raw downloads are the binary, archives
+/// contain it at `binary_path`
+pub(super) fn unpack(
+    name: &str,
+    url: &str,
+    bytes: Vec<u8>,
+    binary_path: Option<&str>,
+) -> Result<Vec<u8>> {
+    let archive = Archive::from_url(url);
+    if let Archive::Raw = archive {
+        return Ok(bytes);
+    }
+
+    let inner = binary_path.ok_or_else(|| {
+        install_error(
+            name,
+            format!("{} is an archive but no binary_path is set", url),
+        )
+    })?;
+    let inner = expand_placeholders(inner);
+    match archive {
+        Archive::TarGz => extract_tar_gz(name, &bytes, &inner),
+        Archive::Zip => extract_zip(name, &bytes, &inner),
+        Archive::Raw => unreachable!(),
+    }
+}
+
+/// Download a URL into memory
+pub(super) fn download(name: &str, url: &str) -> Result<Vec<u8>> {
+    // Enforce HTTPS for security
+    if !url.starts_with("https://") {
+        return Err(install_error(
+            name,
+            format!("Binary URL must use HTTPS: {}", url),
+        ));
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| install_error(name, format!("Failed to create HTTP client: {}", e)))?;
+
+    super::log::note(&format!("GET {}", url));
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| install_error(name, format!("Failed to download from {}: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(install_error(
+            name,
+            format!("HTTP {} from {}", response.status(), url),
+        ));
+    }
+
+    let bytes = response
+        .bytes()
+        .map_err(|e| install_error(name, format!("Failed to read response: {}", e)))?;
+    Ok(bytes.to_vec())
+}
+
+/// Check a download against the digest pinned for `key` (a platform or target)
+/// AIDEV-NOTE: Once any digest is pinned, a key without one is an error rather than
+/// an unverified install. With no digests at all we only warn, so existing bespoke
+/// bottles keep working; prebuilt assets never get here without a digest.
+pub(super) fn verify_checksum(
+    name: &str,
+    url: &str,
+    bytes: &[u8],
+    digests: &HashMap<String, String>,
+    key: &str,
+) -> Result<()> {
+    let Some(expected) = digests.get(key) else {
+        if digests.is_empty() {
+            ui::print_warning(&format!(
+                "No sha256 pinned for {}; download from {} is not verified",
+                name, url
+            ));
+            return Ok(());
+        }
+        return Err(install_error(
+            name,
+            format!("No sha256 digest pinned for {}", key),
+        ));
+    };
+
+    let actual = hex::encode(Sha256::digest(bytes));
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(install_error(
+            name,
+            format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                url,
+                expected.trim(),
+                actual
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Read one file out of a .tar.gz archive
+fn extract_tar_gz(name: &str, bytes: &[u8], inner: &str) -> Result<Vec<u8>> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    let entries = archive
+        .entries()
+        .map_err(|e| install_error(name, format!("Invalid tar.gz archive: {}", e)))?;
+
+    for entry in entries {
+        let mut entry =
+            entry.map_err(|e| install_error(name, format!("Invalid tar.gz archive: {}", e)))?;
+        let path = entry
+            .path()
+            .map_err(|e| install_error(name, format!("Invalid tar.gz archive: {}", e)))?
+            .to_string_lossy()
+            .into_owned();
+
+        if path.trim_start_matches("./") == inner.trim_start_matches("./") {
+            let mut contents = Vec::new();
+            entry
+                .read_to_end(&mut contents)
+                .map_err(|e| install_error(name, format!("Failed to extract {}: {}", inner, e)))?;
+            return Ok(contents);
+        }
+    }
+
+    Err(install_error(
+        name,
+        format!("Archive does not contain {}", inner),
+    ))
+}
+
+/// Read one file out of a .zip archive
+fn extract_zip(name: &str, bytes: &[u8], inner: &str) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| install_error(name, format!("Invalid zip archive: {}", e)))?;
+    let mut file = archive
+        .by_name(inner.trim_start_matches("./"))
+        .map_err(|_| install_error(name, format!("Archive does not contain {}", inner)))?;
+
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)
+        .map_err(|e| install_error(name, format!("Failed to extract {}: {}", inner, e)))?;
+    Ok(contents)
+}
+
+/// Path a custom tool binary is installed to
+pub fn bin_path(name: &str) -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".local").join("bin").join(name))
+}
+
+/// Write the binary to ~/.local/bin and make it executable
+pub(super) fn write_binary(name: &str, contents: &[u8]) -> Result<()> {
+    let bin_path = bin_path(name)
+        .ok_or_else(|| install_error(name, "Could not determine home directory".to_string()))?;
+
+    if let Some(bin_dir) = bin_path.parent() {
+        std::fs::create_dir_all(bin_dir)
+            .map_err(|e| install_error(name, format!("Failed to create bin directory: {}", e)))?;
+    }
+
+    std::fs::write(&bin_path, contents)
+        .map_err(|e| install_error(name, format!("Failed to write binary: {}", e)))?;
+
+    // Make executable
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(e) = std::fs::set_permissions(&bin_path, std::fs::Permissions::from_mode(0o755))
+        {
+            eprintln!(
+                "Warning: could not set executable permissions on {}: {}",
+                bin_path.display(),
+                e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn install_error(name: &str, reason: String) -> BottleError {
+    BottleError::InstallError {
+        tool: name.to_string(),
+        reason,
+    }
+}
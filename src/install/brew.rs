@@ -1,15 +1,221 @@
+//! Homebrew installs with version pinning
+//!
+//! Homebrew has no `install formula --version`. A requested version is honoured by,
+//! in order: a versioned formula (`formula@1.2.3`, `formula@1.2`, `formula@1`, from
+//! core or a tap), or the plain formula when its current version is the one requested
+//! (or, for a semver range, any version in it).
+//! The installed formula is then `brew pin`ned so `brew upgrade` leaves it alone.
+
+use super::log;
 use crate::error::{BottleError, Result};
+use crate::manifest::bottle::BrewPolicy;
+use crate::manifest::state::BrewInstall;
+use crate::ui;
+use crate::version;
 use std::process::Command;
+use std::sync::Mutex;
+
+/// Homebrew refuses to run while another brew process holds its lock, so parallel
+/// installs take turns
+static BREW: Mutex<()> = Mutex::new(());
+
+/// Install a formula at `version` (empty or "latest" for whatever brew has).
+/// `tap` is tapped first when given. What could not be honoured is handled per `policy`.
+pub fn install(
+    tap: Option<&str>,
+    formula: &str,
+    version: &str,
+    policy: BrewPolicy,
+) -> Result<BrewInstall> {
+    let _brew = BREW.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(tap) = tap {
+        run_brew(formula, &["tap", tap])?;
+    }
+
+    // A previous pin would make brew refuse to move this formula
+    unpin_installed(formula);
+
+    if version.is_empty() || version == "latest" {
+        run_brew(formula, &["install", formula])?;
+        return Ok(BrewInstall {
+            formula: formula.to_string(),
+            version: available_version(formula),
+            pinned: false,
+            exact: true,
+        });
+    }
+
+    // 1. A formula whose current version is the one requested (or in the range)
+    if let Some((candidate, found)) = candidates(formula, version).into_iter().find_map(|c| {
+        let found = available_version(&c).filter(|v| version::satisfies(version, v))?;
+        Some((c, found))
+    }) {
+        run_brew(formula, &["install", &candidate])?;
+        if candidate != formula {
+            link_versioned(formula, &candidate);
+        }
+        run_brew(formula, &["pin", &candidate])?;
+        return Ok(BrewInstall {
+            formula: candidate,
+            version: Some(found),
+            pinned: true,
+            exact: true,
+        });
+    }
+
+    // 2. The requested version cannot be honoured
+    let available = available_version(formula);
+    let at = format!(
+        "{} is at {}",
+        formula,
+        available.as_deref().unwrap_or("unknown")
+    );
+    let reason = if version::is_exact(version) {
+        format!(
+            "Homebrew cannot install {} {} (no {}@{} formula; {})",
+            formula, version, formula, version, at
+        )
+    } else {
+        format!("Homebrew cannot install {} {} ({})", formula, version, at)
+    };
+
+    match policy {
+        BrewPolicy::Fail => Err(BottleError::InstallError {
+            tool: formula.to_string(),
+            reason,
+        }),
+        BrewPolicy::Warn => {
+            ui::print_warning(&format!(
+                "{}. Installing {} instead.",
+                reason,
+                available.as_deref().unwrap_or("its current version")
+            ));
+            run_brew(formula, &["install", formula])?;
+            Ok(BrewInstall {
+                formula: formula.to_string(),
+                version: available,
+                pinned: false,
+                exact: false,
+            })
+        }
+    }
+}
+
+/// Uninstall a formula, including any pinned versioned variant (`formula@x.y`)
+pub fn uninstall(formula: &str) -> Result<()> {
+    let _brew = BREW.lock().unwrap_or_else(|e| e.into_inner());
+    let installed = installed_variants(formula);
+    let targets = if installed.is_empty() {
+        vec![formula.to_string()]
+    } else {
+        installed
+    };
+
+    for target in targets {
+        let _ = Command::new("brew").args(["unpin", &target]).output();
+        let status = log::run(Command::new("brew").args(["uninstall", &target])).map_err(|e| {
+            BottleError::UninstallError {
+                tool: formula.to_string(),
+                reason: format!("Failed to run brew: {}", e),
+            }
+        })?;
+
+        if !status.success() {
+            return Err(BottleError::UninstallError {
+                tool: formula.to_string(),
+                reason: format!("brew uninstall exited with code {}", status),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Versioned formula names to try for a version, most specific first
+fn candidates(formula: &str, version: &str) -> Vec<String> {
+    // Versioned formulae are named after versions, so a range can only be met by
+    // the plain formula
+    if !version::is_exact(version) {
+        return vec![formula.to_string()];
+    }
+    let parts: Vec<&str> = version.split('.').collect();
+    let mut names: Vec<String> = (1..=parts.len())
+        .rev()
+        .map(|n| format!("{}@{}", formula, parts[..n].join(".")))
+        .collect();
+    names.push(formula.to_string());
+    names.dedup();
+    names
+}
+
+/// Current stable version of a formula, or None if brew does not know it
+fn available_version(formula: &str) -> Option<String> {
+    let output = Command::new("brew")
+        .args(["info", "--json=v2", formula])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    info.get("formulae")?
+        .get(0)?
+        .get("versions")?
+        .get("stable")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// Installed formulae that are this formula or a versioned variant of it
+fn installed_variants(formula: &str) -> Vec<String> {
+    // `brew list` prints short names, so drop any tap prefix
+    let short = formula.rsplit('/').next().unwrap_or(formula);
+    let versioned = format!("{}@", short);
+
+    Command::new("brew")
+        .args(["list", "--formula", "-1"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|name| *name == short || name.starts_with(&versioned))
+                .map(|name| name.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Versioned formulae are usually keg-only: link the one we installed onto PATH
+/// in place of any other variant
+fn link_versioned(formula: &str, candidate: &str) {
+    for variant in installed_variants(formula) {
+        if variant != candidate.rsplit('/').next().unwrap_or(candidate) {
+            let _ = Command::new("brew").args(["unlink", &variant]).output();
+        }
+    }
+    if run_brew(formula, &["link", "--force", "--overwrite", candidate]).is_err() {
+        ui::print_warning(&format!(
+            "Could not link {}; its binary may not be on PATH",
+            candidate
+        ));
+    }
+}
+
+/// Unpin any installed variant so it can be replaced
+fn unpin_installed(formula: &str) {
+    for variant in installed_variants(formula) {
+        let _ = Command::new("brew").args(["unpin", &variant]).output();
+    }
+}
 
-/// Install a formula using Homebrew
-pub fn install(package: &str, _version: &str) -> Result<()> {
-    // Note: Homebrew doesn't support pinned versions easily
-    // This is a simplified implementation
-    let status = Command::new("brew")
-        .args(["install", package])
-        .status()
-        .map_err(|e| BottleError::InstallError {
-            tool: package.to_string(),
+/// Run brew, turning a failure into an install error for `formula`
+fn run_brew(formula: &str, args: &[&str]) -> Result<()> {
+    let status =
+        log::run(Command::new("brew").args(args)).map_err(|e| BottleError::InstallError {
+            tool: formula.to_string(),
             reason: format!("Failed to run brew: {}", e),
         })?;
 
@@ -17,8 +223,8 @@ pub fn install(package: &str, _version: &str) -> Result<()> {
         Ok(())
     } else {
         Err(BottleError::InstallError {
-            tool: package.to_string(),
-            reason: format!("brew install exited with code {}", status),
+            tool: formula.to_string(),
+            reason: format!("brew {} exited with code {}", args.join(" "), status),
         })
     }
 }
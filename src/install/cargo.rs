@@ -1,3 +1,4 @@
+use super::log;
 use crate::error::{BottleError, Result};
 use std::process::Command;
 
@@ -13,13 +14,10 @@ pub fn install(package: &str, version: &str) -> Result<()> {
     }
 
     let status =
-        Command::new("cargo")
-            .args(&args)
-            .status()
-            .map_err(|e| BottleError::InstallError {
-                tool: package.to_string(),
-                reason: format!("Failed to run cargo: {}", e),
-            })?;
+        log::run(Command::new("cargo").args(&args)).map_err(|e| BottleError::InstallError {
+            tool: package.to_string(),
+            reason: format!("Failed to run cargo: {}", e),
+        })?;
 
     if status.success() {
         Ok(())
@@ -31,7 +29,58 @@ pub fn install(package: &str, version: &str) -> Result<()> {
     }
 }
 
-/// Check if cargo is available
-pub fn is_available() -> bool {
-    which::which("cargo").is_ok()
+/// Install a crate's published release binary with cargo-binstall
+/// AIDEV-NOTE: Only the crate's own release metadata is used: compiling is left to our
+/// `cargo install` fallback, and the third-party quickinstall mirror is not trusted.
+pub fn binstall(package: &str, version: &str) -> Result<()> {
+    let spec = if version.is_empty() || version == "latest" {
+        package.to_string()
+    } else {
+        format!("{}@{}", package, version)
+    };
+
+    let status = log::run(Command::new("cargo").args([
+        "binstall",
+        "--no-confirm",
+        "--disable-strategies",
+        "quick-install,compile",
+        &spec,
+    ]))
+    .map_err(|e| BottleError::InstallError {
+        tool: package.to_string(),
+        reason: format!("Failed to run cargo-binstall: {}", e),
+    })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(BottleError::InstallError {
+            tool: package.to_string(),
+            reason: format!("cargo binstall exited with code {}", status),
+        })
+    }
+}
+
+/// Check if cargo-binstall is available
+pub fn binstall_available() -> bool {
+    which::which("cargo-binstall").is_ok()
+}
+
+/// Uninstall a crate previously installed with cargo
+pub fn uninstall(package: &str) -> Result<()> {
+    let status = log::run(Command::new("cargo").args(["uninstall", package])).map_err(|e| {
+        BottleError::UninstallError {
+            tool: package.to_string(),
+            reason: format!("Failed to run cargo: {}", e),
+        }
+    })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(BottleError::UninstallError {
+            tool: package.to_string(),
+            reason: format!("cargo uninstall exited with code {}", status),
+        })
+    }
 }
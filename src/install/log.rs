@@ -0,0 +1,256 @@
+//! Installer output capture and logs
+//!
+//! The output of the cargo/brew/npm/claude commands run for a tool is captured and
+//! written to `~/.bottle/logs/<timestamp>-<operation>/<tool>.log`, so a failure can
+//! quote it and `bottle logs` can show it later. Capture is per thread: parallel
+//! install workers capture quietly, sequential steps also echo to the terminal.
+
+use crate::error::{BottleError, Result};
+use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Mutex;
+
+/// Lines of captured output quoted in install and uninstall errors
+pub const ERROR_TAIL_LINES: usize = 20;
+
+/// Runs kept in ~/.bottle/logs; older ones are pruned when a new run starts
+const KEEP_RUNS: usize = 20;
+
+/// Output captured on this thread
+struct Capture {
+    log: String,
+    echo: bool,
+}
+
+thread_local! {
+    static CAPTURE: RefCell<Option<Capture>> = const { RefCell::new(None) };
+}
+
+/// The bottle command whose logs are being written
+struct Run {
+    operation: String,
+    started: DateTime<Utc>,
+    /// Created on the first log written, so read-only commands leave nothing behind
+    dir: Option<PathBuf>,
+}
+
+static RUN: Mutex<Option<Run>> = Mutex::new(None);
+
+/// Name the operation logs are filed under (set once per process, from main)
+pub fn set_operation(operation: &str) {
+    *RUN.lock().unwrap_or_else(|e| e.into_inner()) = Some(Run {
+        operation: operation.to_string(),
+        started: Utc::now(),
+        dir: None,
+    });
+}
+
+/// Directory holding one subdirectory per run
+pub fn logs_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".bottle").join("logs"))
+}
+
+/// Run directories, newest first
+pub fn runs() -> Vec<PathBuf> {
+    let Some(dir) = logs_dir() else {
+        return Vec::new();
+    };
+    let mut runs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    // Names start with a sortable timestamp
+    runs.sort();
+    runs.reverse();
+    runs
+}
+
+/// Log file for `tool` within a run directory
+pub fn log_path(run: &Path, tool: &str) -> PathBuf {
+    let name: String = tool
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    run.join(format!("{}.log", name))
+}
+
+/// Run `f` with the output of the commands it runs captured into `<tool>.log`.
+/// `echo` also streams that output to the terminal. An install or uninstall error
+/// from `f` gets the tail of the log appended to its reason.
+pub fn capture<T>(tool: &str, echo: bool, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    // Nested captures belong to the outer one
+    if CAPTURE.with(|c| c.borrow().is_some()) {
+        return f();
+    }
+
+    CAPTURE.with(|c| {
+        *c.borrow_mut() = Some(Capture {
+            log: String::new(),
+            echo,
+        })
+    });
+    let result = f();
+    let log = CAPTURE
+        .with(|c| c.borrow_mut().take())
+        .map(|c| c.log)
+        .unwrap_or_default();
+
+    if log.is_empty() {
+        return result;
+    }
+    let path = write_log(tool, &log);
+    result.map_err(|e| with_log_tail(e, &log, path.as_deref()))
+}
+
+/// Add a line to this thread's log (ignored when not capturing)
+pub fn note(line: &str) {
+    CAPTURE.with(|c| {
+        if let Some(capture) = c.borrow_mut().as_mut() {
+            capture.log.push_str(line);
+            capture.log.push('\n');
+        }
+    });
+}
+
+/// Run a command to completion. While this thread is capturing, its stdout and
+/// stderr are recorded (and echoed if asked); otherwise they go to the terminal.
+pub fn run(cmd: &mut Command) -> io::Result<ExitStatus> {
+    let Some(echo) = CAPTURE.with(|c| c.borrow().as_ref().map(|c| c.echo)) else {
+        return cmd.status();
+    };
+
+    note(&format!("$ {}", describe(cmd)));
+    if !echo {
+        cmd.stdin(Stdio::null());
+    }
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    // Interleave both streams into one log in the order lines arrive
+    let lines = Mutex::new(String::new());
+    std::thread::scope(|scope| {
+        if let Some(stdout) = stdout {
+            scope.spawn(|| pump(stdout, &lines, echo.then(io::stdout)));
+        }
+        if let Some(stderr) = stderr {
+            scope.spawn(|| pump(stderr, &lines, echo.then(io::stderr)));
+        }
+    });
+    let status = child.wait()?;
+
+    let lines = lines.into_inner().unwrap_or_else(|e| e.into_inner());
+    CAPTURE.with(|c| {
+        if let Some(capture) = c.borrow_mut().as_mut() {
+            capture.log.push_str(&lines);
+        }
+    });
+    note(&format!("[{}]", status));
+    Ok(status)
+}
+
+/// The last `lines` lines of a log
+pub fn tail(log: &str, lines: usize) -> Vec<&str> {
+    let all: Vec<&str> = log.lines().collect();
+    all[all.len().saturating_sub(lines)..].to_vec()
+}
+
+/// Copy a child's output stream into the shared log, line by line
+fn pump(stream: impl Read, lines: &Mutex<String>, mut echo: Option<impl Write>) {
+    for line in BufReader::new(stream).split(b'\n').map_while(|l| l.ok()) {
+        let line = String::from_utf8_lossy(&line);
+        if let Some(out) = echo.as_mut() {
+            let _ = writeln!(out, "{}", line);
+        }
+        let mut lines = lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+}
+
+/// Append to `<tool>.log` in this run's directory, returning its path
+fn write_log(tool: &str, log: &str) -> Option<PathBuf> {
+    let dir = run_dir()?;
+    let path = log_path(&dir, tool);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .ok()?;
+    file.write_all(log.as_bytes()).ok()?;
+    Some(path)
+}
+
+/// This run's log directory, creating it (and pruning old runs) on first use
+fn run_dir() -> Option<PathBuf> {
+    let mut run = RUN.lock().unwrap_or_else(|e| e.into_inner());
+    let run = run.as_mut()?;
+    if let Some(dir) = &run.dir {
+        return Some(dir.clone());
+    }
+
+    let dir = logs_dir()?.join(format!(
+        "{}-{}",
+        run.started.format("%Y%m%dT%H%M%SZ"),
+        run.operation
+    ));
+    std::fs::create_dir_all(&dir).ok()?;
+    prune(&dir);
+    run.dir = Some(dir.clone());
+    Some(dir)
+}
+
+/// Remove all but the newest runs
+fn prune(current: &Path) {
+    for old in runs().into_iter().skip(KEEP_RUNS) {
+        if old != current {
+            let _ = std::fs::remove_dir_all(old);
+        }
+    }
+}
+
+/// Quote the end of the log in an install or uninstall error
+fn with_log_tail(error: BottleError, log: &str, path: Option<&Path>) -> BottleError {
+    let mut quoted = String::new();
+    for line in tail(log, ERROR_TAIL_LINES) {
+        quoted.push_str(&format!("\n    │ {}", line));
+    }
+    if let Some(path) = path {
+        quoted.push_str(&format!("\n    Full log: {}", path.display()));
+    }
+
+    match error {
+        BottleError::InstallError { tool, reason } => BottleError::InstallError {
+            tool,
+            reason: reason + &quoted,
+        },
+        BottleError::UninstallError { tool, reason } => BottleError::UninstallError {
+            tool,
+            reason: reason + &quoted,
+        },
+        other => other,
+    }
+}
+
+/// Command line for the log
+fn describe(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|s| s.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
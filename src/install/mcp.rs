@@ -1,9 +1,14 @@
+use super::log;
 use crate::error::{BottleError, Result};
 use crate::manifest::bottle::McpServerDef;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashMap;
 use std::process::Command;
+use std::sync::Mutex;
+
+/// `claude mcp add/remove` rewrite the same config file, so parallel installs take turns
+static CLAUDE_CONFIG: Mutex<()> = Mutex::new(());
 
 /// Pattern for matching ${VAR} environment variable references
 static ENV_VAR_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{([^}]+)\}").unwrap());
@@ -92,6 +97,8 @@ pub fn register_bespoke(name: &str, server: &McpServerDef) -> Result<()> {
     }
 
     // Build and run the command
+    // AIDEV-NOTE: Not run through `log::run`: the command line and claude's echo of it
+    // carry expanded ${VAR} values (often API keys), which must not land in log files.
     let status =
         Command::new("claude")
             .args(&args)
@@ -217,23 +224,22 @@ pub fn register_bespoke_opencode(servers: &HashMap<String, McpServerDef>) -> Res
 /// `name` is the MCP server name (e.g., "oh-mcp")
 /// `package` is the npm package (e.g., "@cloud-atlas-ai/oh-mcp-server")
 pub fn register(name: &str, package: &str, version: &str) -> Result<()> {
-    let status = Command::new("claude")
-        .args([
-            "mcp",
-            "add",
-            name,
-            "-s",
-            "user",
-            "--",
-            "npx",
-            "-y",
-            &format!("{}@{}", package, version),
-        ])
-        .status()
-        .map_err(|e| BottleError::InstallError {
-            tool: name.to_string(),
-            reason: format!("Failed to run claude mcp add: {}", e),
-        })?;
+    let _config = CLAUDE_CONFIG.lock().unwrap_or_else(|e| e.into_inner());
+    let status = log::run(Command::new("claude").args([
+        "mcp",
+        "add",
+        name,
+        "-s",
+        "user",
+        "--",
+        "npx",
+        "-y",
+        &format!("{}@{}", package, version),
+    ]))
+    .map_err(|e| BottleError::InstallError {
+        tool: name.to_string(),
+        reason: format!("Failed to run claude mcp add: {}", e),
+    })?;
 
     if status.success() {
         Ok(())
@@ -247,13 +253,13 @@ pub fn register(name: &str, package: &str, version: &str) -> Result<()> {
 
 /// Unregister an MCP server
 pub fn unregister(name: &str) -> Result<()> {
-    let status = Command::new("claude")
-        .args(["mcp", "remove", name])
-        .status()
-        .map_err(|e| BottleError::InstallError {
+    let _config = CLAUDE_CONFIG.lock().unwrap_or_else(|e| e.into_inner());
+    let status = log::run(Command::new("claude").args(["mcp", "remove", name])).map_err(|e| {
+        BottleError::InstallError {
             tool: name.to_string(),
             reason: format!("Failed to run claude mcp remove: {}", e),
-        })?;
+        }
+    })?;
 
     if status.success() {
         Ok(())
@@ -264,3 +270,21 @@ pub fn unregister(name: &str) -> Result<()> {
         })
     }
 }
+
+/// Check if an MCP server is registered with Claude Code
+pub fn is_registered(name: &str) -> bool {
+    Command::new("claude")
+        .args(["mcp", "list"])
+        .output()
+        .map(|o| lists_server(&String::from_utf8_lossy(&o.stdout), name))
+        .unwrap_or(false)
+}
+
+/// Whether `claude mcp list` output has a line for exactly this server (`<name>: ...`)
+fn lists_server(output: &str, name: &str) -> bool {
+    output.lines().any(|line| {
+        line.trim_start()
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
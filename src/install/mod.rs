@@ -1,32 +1,140 @@
+pub mod binary;
 pub mod brew;
 pub mod cargo;
+pub mod log;
 pub mod mcp;
+pub mod parallel;
 pub mod plugin;
+pub mod prebuilt;
+pub mod requires;
 
-use crate::error::Result;
-use crate::manifest::state::InstallMethod;
+use crate::error::{BottleError, Result};
+use crate::manifest::bottle::BrewPolicy;
+use crate::manifest::state::{BrewInstall, InstallMethod};
 use crate::manifest::tool::{ToolDefinition, ToolType};
+use crate::version;
 
-/// Install a tool using the appropriate method, returns the method actually used
-pub fn install_tool(tool: &ToolDefinition, version: &str) -> Result<InstallMethod> {
+/// What an install did
+#[derive(Debug, Clone)]
+pub struct Installed {
+    pub method: InstallMethod,
+    /// Homebrew outcome (brew installs only)
+    pub brew: Option<BrewInstall>,
+}
+
+impl From<InstallMethod> for Installed {
+    fn from(method: InstallMethod) -> Self {
+        Installed { method, brew: None }
+    }
+}
+
+/// Install a tool using the appropriate method, returns what was actually done.
+/// `brew_policy` decides what happens when Homebrew cannot honour `version`.
+pub fn install_tool(
+    tool: &ToolDefinition,
+    version: &str,
+    brew_policy: BrewPolicy,
+) -> Result<Installed> {
     match tool.tool_type {
         ToolType::Binary => {
-            // Try cargo first, fall back to brew
+            // 1. A release asset for this host, verified against its pinned digest
+            if let Some(asset) = prebuilt::find_asset(tool, version) {
+                prebuilt::install(tool, version, &asset)?;
+                return Ok(InstallMethod::Prebuilt.into());
+            }
+
+            // 2. cargo-binstall, when present, for crates with published binaries
+            if tool.registry == "crates.io" && cargo::binstall_available() {
+                match cargo::binstall(&tool.package, version) {
+                    Ok(()) => return Ok(InstallMethod::Binstall.into()),
+                    Err(e) => crate::ui::print_warning(&format!(
+                        "{}; building {} from source",
+                        e, tool.name
+                    )),
+                }
+            }
+
+            // 3. Build from source with cargo, fall back to brew
             if which::which("cargo").is_ok() {
                 cargo::install(&tool.package, version)?;
-                Ok(InstallMethod::Cargo)
+                Ok(InstallMethod::Cargo.into())
             } else if which::which("brew").is_ok() {
-                brew::install(&tool.package, version)?;
-                Ok(InstallMethod::Brew)
+                let (tap, formula) = tool.brew_formula();
+                let outcome = brew::install(tap.as_deref(), &formula, version, brew_policy)?;
+                Ok(Installed {
+                    method: InstallMethod::Brew,
+                    brew: Some(outcome),
+                })
             } else {
-                Err(crate::error::BottleError::PrerequisitesNotMet(
+                Err(BottleError::PrerequisitesNotMet(
                     "Neither cargo nor brew found. Install Rust or Homebrew.".into(),
                 ))
             }
         }
         ToolType::Mcp => {
             mcp::register(&tool.name, &tool.package, version)?;
-            Ok(InstallMethod::Mcp)
+            Ok(InstallMethod::Mcp.into())
         }
     }
 }
+
+/// Uninstall a tool using the method it was installed with
+pub fn uninstall_tool(tool: &ToolDefinition, method: &InstallMethod) -> Result<()> {
+    match method {
+        InstallMethod::Cargo | InstallMethod::Binstall => cargo::uninstall(&tool.package),
+        InstallMethod::Prebuilt => prebuilt::uninstall(tool),
+        InstallMethod::Brew => brew::uninstall(&tool.brew_formula().1),
+        InstallMethod::Mcp => mcp::unregister(&tool.name),
+    }
+}
+
+/// Check if a tool is already present on this machine (binary on PATH or MCP registered)
+pub fn is_present(tool: &ToolDefinition) -> bool {
+    match tool.tool_type {
+        ToolType::Binary => which::which(tool.binary.as_deref().unwrap_or(&tool.name)).is_ok(),
+        ToolType::Mcp => mcp::is_registered(&tool.name),
+    }
+}
+
+/// Run a tool's `check` command and parse the version it reports.
+/// Returns None if the command is missing, fails, or prints no version (and for MCP
+/// servers, whose check is a registration lookup rather than a version).
+pub fn installed_version(tool: &ToolDefinition) -> Option<String> {
+    if matches!(tool.tool_type, ToolType::Mcp) {
+        return None;
+    }
+
+    let mut parts = tool.check.split_whitespace();
+    let program = parts.next()?;
+
+    let output = std::process::Command::new(program)
+        .args(parts)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Observe the version a fresh install reports, warning if it is not the one requested
+/// AIDEV-NOTE: brew in particular may install a tap's latest instead of the pinned
+/// version; recording what the binary reports lets `bottle status` flag that.
+pub fn observe_installed_version(tool: &ToolDefinition, requested: &str) -> Option<String> {
+    let observed = installed_version(tool)?;
+    if !version::satisfies(requested, &observed) {
+        crate::ui::print_warning(&format!(
+            "{} reports version {} (requested {})",
+            tool.name, observed, requested
+        ));
+    }
+    Some(observed)
+}
+
+/// Find the first version-looking token in `--version` output ("ba 0.2.1", "v1.4.0")
+pub fn parse_version(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|s| s.trim_start_matches('v').trim_end_matches([',', ')', ';']))
+        .find(|s| s.chars().next().is_some_and(|c| c.is_ascii_digit()))
+        .map(|s| s.to_string())
+}
@@ -0,0 +1,317 @@
+//! Concurrent tool installs
+//!
+//! Tools in a bottle install on a bounded pool of worker threads with one progress
+//! line each; a tool waits for any tool its definition `requires`. Workers capture
+//! the output of the commands they run into each tool's log (see `log`) instead of
+//! the terminal.
+
+use super::{log, requires, Installed};
+use crate::config::Config;
+use crate::error::{BottleError, Result};
+use crate::fetch::fetch_tool_definition;
+use crate::manifest::bottle::BrewPolicy;
+use crate::manifest::tool::ToolDefinition;
+use crate::ui;
+use console::style;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// Tools installed at once unless `install_jobs` is set in ~/.bottle/config.toml
+pub const DEFAULT_JOBS: usize = 4;
+
+/// A tool to install
+pub struct Job {
+    pub tool: String,
+    pub version: String,
+    /// Shown when the install succeeds ("installed", "updated")
+    pub done: &'static str,
+}
+
+/// A successful install
+pub struct Installation {
+    pub installed: Installed,
+    pub observed_version: Option<String>,
+    /// The tool was on this machine before the install (only checked when asked)
+    pub preexisting: bool,
+}
+
+/// Result of one job
+pub struct Outcome {
+    pub tool: String,
+    pub version: String,
+    pub result: Result<Installation>,
+}
+
+/// Install tools concurrently, returning outcomes in job order.
+/// A job whose tool definition `requires` another job's tool starts only after that
+/// job succeeds, and fails without running if it did not.
+/// With `fail_fast` no new install starts after a failure (running ones finish) and
+/// jobs that never started are left out of the outcomes. `detect_preexisting`
+/// checks whether each tool was already present, for --strict rollback.
+/// AIDEV-NOTE: `cargo install` builds every crate in its own temporary target dir
+/// and locks the shared registry cache itself, so concurrent builds are safe.
+/// Homebrew and `claude mcp` are not; brew.rs and mcp.rs make those take turns.
+pub fn install_all(
+    jobs: &[Job],
+    brew_policy: BrewPolicy,
+    fail_fast: bool,
+    detect_preexisting: bool,
+) -> Vec<Outcome> {
+    // 1. Definitions up front: they say what each job waits for
+    let mut defs = Vec::new();
+    let mut schedule = Schedule {
+        slots: Vec::new(),
+        deps: Vec::new(),
+        stopped: false,
+    };
+    let mut fetch_failures = Vec::new();
+    for (index, job) in jobs.iter().enumerate() {
+        match fetch_tool_definition(&job.tool) {
+            Ok(def) => {
+                schedule.deps.push(
+                    def.requirements()
+                        .iter()
+                        .filter_map(|r| jobs.iter().position(|j| j.tool == r.name))
+                        .collect(),
+                );
+                schedule.slots.push(Slot::Pending);
+                defs.push(Some(def));
+            }
+            Err(e) => {
+                schedule.deps.push(Vec::new());
+                schedule.slots.push(Slot::Done(false));
+                defs.push(None);
+                fetch_failures.push((index, e));
+            }
+        }
+    }
+
+    // 2. One progress line per job, in the order they can run
+    let display = requires::order(&schedule.deps).unwrap_or_else(|_| (0..jobs.len()).collect());
+    let progress = ui::progress_group();
+    let mut bars: Vec<Option<ProgressBar>> = vec![None; jobs.len()];
+    for &index in &display {
+        bars[index] = Some(add_bar(&progress, &jobs[index]));
+    }
+    let bars: Vec<ProgressBar> = bars.into_iter().flatten().collect();
+
+    let mut outcomes: Vec<(usize, Outcome)> = Vec::new();
+    for (index, e) in fetch_failures {
+        finish_bar(
+            &progress,
+            &bars[index],
+            &jobs[index],
+            style("failed").red().to_string(),
+        );
+        outcomes.push((index, outcome(&jobs[index], Err(e))));
+    }
+    if fail_fast && !outcomes.is_empty() {
+        schedule.stopped = true;
+    }
+
+    // 3. Workers take the first job whose requirements are done
+    let schedule = Mutex::new(schedule);
+    let changed = Condvar::new();
+    let outcomes = Mutex::new(outcomes);
+    let workers = jobs_limit().clamp(1, jobs.len().max(1));
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while let Some((index, blocked)) = next_job(&schedule, &changed, jobs) {
+                    let job = &jobs[index];
+                    let bar = &bars[index];
+
+                    let result = match (blocked, &defs[index]) {
+                        (Some(e), _) => Err(e),
+                        (None, None) => unreachable!("jobs without a definition start done"),
+                        (None, Some(def)) => {
+                            bar.set_message(format!(
+                                "{} {}",
+                                style(&job.version).dim(),
+                                style("installing").dim()
+                            ));
+                            bar.enable_steady_tick(Duration::from_millis(100));
+                            log::capture(&job.tool, false, || {
+                                install_one(def, job, brew_policy, detect_preexisting)
+                            })
+                        }
+                    };
+
+                    let status = match &result {
+                        Ok(_) => style(job.done).green(),
+                        Err(_) => style("failed").red(),
+                    };
+                    finish_bar(&progress, bar, job, status.to_string());
+
+                    {
+                        let mut schedule = schedule.lock().unwrap_or_else(|e| e.into_inner());
+                        schedule.slots[index] = Slot::Done(result.is_ok());
+                        if result.is_err() && fail_fast {
+                            schedule.stopped = true;
+                        }
+                    }
+                    changed.notify_all();
+
+                    outcomes
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push((index, outcome(job, result)));
+                }
+            });
+        }
+    });
+
+    // Jobs that never started because an earlier one failed
+    for (job, bar) in jobs.iter().zip(&bars) {
+        if !bar.is_finished() {
+            finish_bar(&progress, bar, job, style("skipped").dim().to_string());
+        }
+    }
+    ui::end_progress_group();
+
+    let mut outcomes = outcomes.into_inner().unwrap_or_else(|e| e.into_inner());
+    outcomes.sort_by_key(|(index, _)| *index);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+/// Progress of one job
+#[derive(Clone, Copy, PartialEq)]
+enum Slot {
+    Pending,
+    Running,
+    /// Finished; true if it succeeded
+    Done(bool),
+}
+
+struct Schedule {
+    slots: Vec<Slot>,
+    /// Indices of the jobs each job requires
+    deps: Vec<Vec<usize>>,
+    /// Set after a failure with `fail_fast`
+    stopped: bool,
+}
+
+/// Claim the next job to run, waiting while the only pending jobs are blocked on
+/// running ones. Returns the job's index, plus an error instead if a requirement
+/// failed (or can never be met). None when there is nothing left to start.
+fn next_job(
+    schedule: &Mutex<Schedule>,
+    changed: &Condvar,
+    jobs: &[Job],
+) -> Option<(usize, Option<BottleError>)> {
+    let mut schedule = schedule.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        if schedule.stopped {
+            return None;
+        }
+
+        let pending: Vec<usize> = (0..jobs.len())
+            .filter(|&i| schedule.slots[i] == Slot::Pending)
+            .collect();
+        if pending.is_empty() {
+            return None;
+        }
+
+        for &index in &pending {
+            let deps = &schedule.deps[index];
+            if let Some(&failed) = deps
+                .iter()
+                .find(|&&d| schedule.slots[d] == Slot::Done(false))
+            {
+                schedule.slots[index] = Slot::Running;
+                return Some((
+                    index,
+                    Some(blocked(
+                        &jobs[index],
+                        format!("requires {}, which failed to install", jobs[failed].tool),
+                    )),
+                ));
+            }
+            if deps.iter().all(|&d| schedule.slots[d] == Slot::Done(true)) {
+                schedule.slots[index] = Slot::Running;
+                return Some((index, None));
+            }
+        }
+
+        // Everything left waits on something: fine while jobs are running, a
+        // cycle otherwise (check_prerequisites reports those before installing)
+        if !schedule.slots.contains(&Slot::Running) {
+            let index = pending[0];
+            schedule.slots[index] = Slot::Running;
+            return Some((
+                index,
+                Some(blocked(
+                    &jobs[index],
+                    "its requirements form a cycle".to_string(),
+                )),
+            ));
+        }
+        schedule = changed.wait(schedule).unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+fn blocked(job: &Job, reason: String) -> BottleError {
+    BottleError::InstallError {
+        tool: job.tool.clone(),
+        reason: format!("Not installed: {}", reason),
+    }
+}
+
+fn outcome(job: &Job, result: Result<Installation>) -> Outcome {
+    Outcome {
+        tool: job.tool.clone(),
+        version: job.version.clone(),
+        result,
+    }
+}
+
+fn install_one(
+    tool_def: &ToolDefinition,
+    job: &Job,
+    brew_policy: BrewPolicy,
+    detect_preexisting: bool,
+) -> Result<Installation> {
+    let preexisting = detect_preexisting && super::is_present(tool_def);
+    let installed = super::install_tool(tool_def, &job.version, brew_policy)?;
+    let observed_version = super::observe_installed_version(tool_def, &job.version);
+    Ok(Installation {
+        installed,
+        observed_version,
+        preexisting,
+    })
+}
+
+fn jobs_limit() -> usize {
+    Config::load()
+        .ok()
+        .and_then(|c| c.install_jobs)
+        .unwrap_or(DEFAULT_JOBS)
+}
+
+fn add_bar(progress: &MultiProgress, job: &Job) -> ProgressBar {
+    let bar = progress.add(ProgressBar::new_spinner());
+    bar.set_style(
+        ProgressStyle::default_spinner()
+            .template("  {prefix:<12} {msg} {spinner:.blue}")
+            .expect("Invalid progress template"),
+    );
+    bar.set_prefix(job.tool.clone());
+    bar.set_message(format!(
+        "{} {}",
+        style(&job.version).dim(),
+        style("queued").dim()
+    ));
+    bar
+}
+
+/// Finish a job's line. Without a terminal the bars are hidden, so the line is
+/// printed instead.
+fn finish_bar(progress: &MultiProgress, bar: &ProgressBar, job: &Job, status: String) {
+    let message = format!("{} {}", style(&job.version).dim(), status);
+    if progress.is_hidden() {
+        println!("  {:<12} {}", job.tool, message);
+    }
+    bar.finish_with_message(message);
+}
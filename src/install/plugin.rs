@@ -1,15 +1,18 @@
+use super::log;
 use crate::error::{BottleError, Result};
 use std::process::Command;
 
 /// Install a Claude Code plugin
 pub fn install(plugin: &str, marketplace: &str) -> Result<()> {
-    let status = Command::new("claude")
-        .args(["plugin", "install", &format!("{}@{}", plugin, marketplace)])
-        .status()
-        .map_err(|e| BottleError::InstallError {
-            tool: plugin.to_string(),
-            reason: format!("Failed to run claude plugin install: {}", e),
-        })?;
+    let status = log::run(Command::new("claude").args([
+        "plugin",
+        "install",
+        &format!("{}@{}", plugin, marketplace),
+    ]))
+    .map_err(|e| BottleError::InstallError {
+        tool: plugin.to_string(),
+        reason: format!("Failed to run claude plugin install: {}", e),
+    })?;
 
     if status.success() {
         Ok(())
@@ -0,0 +1,133 @@
+//! Prebuilt release binaries for curated tools
+//!
+//! A tool definition's `prebuilt` block names a release asset per Rust target triple.
+//! When the host has one with a digest pinned for the requested version, the asset is
+//! downloaded, checked against that digest and written to ~/.local/bin, skipping the
+//! source build.
+
+use super::binary;
+use crate::error::{BottleError, Result};
+use crate::manifest::tool::{Prebuilt, ToolDefinition};
+use crate::version;
+
+/// A release asset chosen for this host
+pub struct Asset {
+    pub target: String,
+    pub url: String,
+}
+
+/// Target triples this host can run, preferred first
+/// AIDEV-NOTE: glibc Linux hosts also run static musl builds, so those are accepted
+/// when a release only ships musl assets.
+pub fn host_targets() -> Vec<String> {
+    let arch = std::env::consts::ARCH;
+    match std::env::consts::OS {
+        "macos" => vec![format!("{}-apple-darwin", arch)],
+        "windows" => vec![format!("{}-pc-windows-msvc", arch)],
+        "linux" if cfg!(target_env = "musl") => vec![format!("{}-unknown-linux-musl", arch)],
+        "linux" => vec![
+            format!("{}-unknown-linux-gnu", arch),
+            format!("{}-unknown-linux-musl", arch),
+        ],
+        os => vec![format!("{}-unknown-{}", arch, os)],
+    }
+}
+
+/// The release asset for this host, or None when the tool has no prebuilt for it.
+/// "latest" and ranges have no one release to point at, so they build from source.
+/// AIDEV-NOTE: An asset without a sha256 pinned for this version and target is not a
+/// prebuilt at all: curated tools are never installed from an unverified download
+/// (unlike `binary_url` custom tools, which only warn). Bumping a pin without adding
+/// digests therefore builds from source until they're added.
+pub fn find_asset(tool: &ToolDefinition, version: &str) -> Option<Asset> {
+    let prebuilt = tool.prebuilt.as_ref()?;
+    if !version::is_exact(version) {
+        return None;
+    }
+    let digests = prebuilt.sha256.get(version)?;
+
+    host_targets().into_iter().find_map(|target| {
+        digests.get(&target)?;
+        let asset = expand(prebuilt.assets.get(&target)?, version, &target);
+        let url = expand(&prebuilt.url, version, &target).replace("{asset}", &asset);
+        Some(Asset { target, url })
+    })
+}
+
+/// Download, verify and install a prebuilt asset
+pub fn install(tool: &ToolDefinition, version: &str, asset: &Asset) -> Result<()> {
+    let prebuilt = tool
+        .prebuilt
+        .as_ref()
+        .ok_or_else(|| BottleError::InstallError {
+            tool: tool.name.clone(),
+            reason: "No prebuilt binaries in tool definition".to_string(),
+        })?;
+    let binary_name = binary_name(tool);
+
+    // 1. Download (HTTPS only)
+    let bytes = binary::download(&tool.name, &asset.url)?;
+
+    // 2. Verify against the digest pinned for this version and target (`find_asset`
+    // only offers assets that have one)
+    let digests = prebuilt
+        .sha256
+        .get(version)
+        .ok_or_else(|| BottleError::InstallError {
+            tool: tool.name.clone(),
+            reason: format!("No sha256 digests pinned for prebuilt {}", version),
+        })?;
+    binary::verify_checksum(&tool.name, &asset.url, &bytes, digests, &asset.target)?;
+
+    // 3. Unpack; archives default to the binary at their root
+    let inner = archive_path(prebuilt, &binary_name, version, &asset.target);
+    let contents = binary::unpack(&tool.name, &asset.url, bytes, Some(&inner))?;
+
+    // 4. Write to ~/.local/bin
+    binary::write_binary(&binary_name, &contents)?;
+
+    if which::which(&binary_name).is_err() {
+        crate::ui::print_warning(&format!(
+            "Installed {} to ~/.local/bin, which is not on your PATH",
+            binary_name
+        ));
+    }
+    Ok(())
+}
+
+/// Remove a prebuilt binary from ~/.local/bin
+pub fn uninstall(tool: &ToolDefinition) -> Result<()> {
+    let binary_name = binary_name(tool);
+    let path = binary::bin_path(&binary_name).ok_or_else(|| BottleError::UninstallError {
+        tool: tool.name.clone(),
+        reason: "Could not determine home directory".to_string(),
+    })?;
+
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(BottleError::UninstallError {
+            tool: tool.name.clone(),
+            reason: format!("Failed to remove {}: {}", path.display(), e),
+        }),
+    }
+}
+
+/// Name of the installed binary
+pub fn binary_name(tool: &ToolDefinition) -> String {
+    tool.binary.clone().unwrap_or_else(|| tool.name.clone())
+}
+
+fn archive_path(prebuilt: &Prebuilt, binary_name: &str, version: &str, target: &str) -> String {
+    prebuilt
+        .binary_path
+        .as_deref()
+        .map(|p| expand(p, version, target))
+        .unwrap_or_else(|| binary_name.to_string())
+}
+
+fn expand(template: &str, version: &str, target: &str) -> String {
+    template
+        .replace("{version}", version)
+        .replace("{target}", target)
+}
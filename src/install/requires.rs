@@ -0,0 +1,71 @@
+//! Tool requirements: install ordering and system prerequisites
+//!
+//! A `requires` entry naming another tool in the bottle orders the installs; any
+//! other entry is a binary that must already be on PATH, optionally at a minimum
+//! version (`node>=18`).
+
+use crate::manifest::tool::Requirement;
+use crate::ui;
+use crate::version;
+use std::collections::BTreeSet;
+use std::process::Command;
+
+/// Check a system binary requirement, returning what is wrong with it
+pub fn check_system(requirement: &Requirement) -> Option<String> {
+    if which::which(&requirement.name).is_err() {
+        return Some(format!("{} is not installed", requirement.name));
+    }
+
+    let min = requirement.min_version.as_deref()?;
+    match system_version(&requirement.name) {
+        Some(found) if version::at_least(&found, min) => None,
+        Some(found) => Some(format!(
+            "{} {} is installed but {} is needed",
+            requirement.name, found, requirement
+        )),
+        None => {
+            ui::print_warning(&format!(
+                "Could not determine the version of {} (need {}); continuing",
+                requirement.name, requirement
+            ));
+            None
+        }
+    }
+}
+
+/// Installed version of a system binary, from `<binary> --version`
+fn system_version(binary: &str) -> Option<String> {
+    let output = Command::new(binary)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    // Some tools print their version on stderr
+    super::parse_version(&String::from_utf8_lossy(&output.stdout))
+        .or_else(|| super::parse_version(&String::from_utf8_lossy(&output.stderr)))
+}
+
+/// Order items so each comes after its dependencies (`deps[i]` are indices into the
+/// same list), keeping the original order otherwise. On a cycle, returns the items
+/// that could not be ordered.
+pub fn order(deps: &[Vec<usize>]) -> std::result::Result<Vec<usize>, Vec<usize>> {
+    let mut remaining: BTreeSet<usize> = (0..deps.len()).collect();
+    let mut ordered = Vec::with_capacity(deps.len());
+
+    while !remaining.is_empty() {
+        let ready = remaining
+            .iter()
+            .copied()
+            .find(|&i| deps[i].iter().all(|d| !remaining.contains(d)));
+        match ready {
+            Some(i) => {
+                remaining.remove(&i);
+                ordered.push(i);
+            }
+            None => return Err(remaining.into_iter().collect()),
+        }
+    }
+
+    Ok(ordered)
+}
@@ -104,10 +104,9 @@ fn get_installed_plugins_at(config_dir: &Path) -> Vec<String> {
         .unwrap_or_default()
 }
 
-/// Get list of missing plugins (not currently installed)
-#[allow(dead_code)]
-pub fn get_missing_plugins() -> Vec<String> {
-    let installed = get_installed_plugins();
+/// Get list of bottle plugins missing from a specific Claude directory
+pub fn missing_plugins_at(config_dir: &Path) -> Vec<String> {
+    let installed = get_installed_plugins_at(config_dir);
     ALL_PLUGINS
         .iter()
         .filter(|plugin| {
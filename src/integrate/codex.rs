@@ -1,36 +1,27 @@
 //! Codex integration
 //!
 //! Installs/removes all Open Horizon Labs skills for Codex (bottle, ba, wm, superego).
-//! Skills are fetched from GitHub at runtime to allow updates without new binary releases.
+//! Skills are fetched from the registry at runtime to allow updates without new binary releases.
 
 use crate::error::{BottleError, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
 
-const GITHUB_RAW_BASE: &str =
-    "https://raw.githubusercontent.com/open-horizon-labs/bottle/master/codex-skill";
-const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Registry directory holding the Codex skills
+const SKILL_DIR: &str = "codex-skill";
 
-/// Fetch a skill file from GitHub
+/// Fetch a skill file from the registry (cached like manifests)
 fn fetch_skill(path: &str) -> Result<String> {
-    let url = format!("{}/{}", GITHUB_RAW_BASE, path);
-    let client = reqwest::blocking::Client::builder()
-        .timeout(REQUEST_TIMEOUT)
-        .build()
-        .map_err(|e| BottleError::InstallError {
-            tool: "codex integration".to_string(),
-            reason: format!("Failed to create HTTP client: {}", e),
-        })?;
-    client
-        .get(&url)
-        .send()
-        .and_then(|r| r.error_for_status())
-        .and_then(|r| r.text())
-        .map_err(|e| BottleError::InstallError {
-            tool: "codex integration".to_string(),
-            reason: format!("Failed to fetch {}: {}", path, e),
-        })
+    let fail = |reason: String| BottleError::InstallError {
+        tool: "codex integration".to_string(),
+        reason,
+    };
+
+    match crate::fetch::fetch_registry_file(&format!("{}/{}", SKILL_DIR, path)) {
+        Ok(Some(contents)) => Ok(contents),
+        Ok(None) => Err(fail(format!("{} not found in registry", path))),
+        Err(e) => Err(fail(format!("Failed to fetch {}: {}", path, e))),
+    }
 }
 
 /// Get the Codex skills directory path
@@ -46,7 +37,6 @@ pub fn is_detected() -> bool {
 }
 
 /// Check if the bottle skill is installed in Codex
-#[allow(dead_code)]
 pub fn is_installed() -> bool {
     skills_dir()
         .map(|s| s.join("bottle").exists())
@@ -66,7 +56,7 @@ pub fn install() -> Result<()> {
         reason: format!("Failed to create skills directory: {}", e),
     })?;
 
-    // Fetch and install all skills from GitHub
+    // Fetch and install all skills from the registry
     let bottle_skill = fetch_skill("SKILL.md")?;
     let ba_skill = fetch_skill("ba/SKILL.md")?;
     let wm_skill = fetch_skill("wm/SKILL.md")?;
@@ -110,7 +100,7 @@ fn install_skill(skills_path: &Path, name: &str, content: &str) -> Result<()> {
 }
 
 /// Update all Open Horizon Labs skills for Codex
-/// Re-fetches all skills from GitHub to get latest versions
+/// Re-fetches all skills from the registry to get latest versions
 pub fn update() -> Result<()> {
     install()
 }
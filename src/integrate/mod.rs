@@ -24,6 +24,13 @@ impl Platform {
         }
     }
 
+    /// Look up a platform by its state key
+    pub fn from_key(key: &str) -> Option<Self> {
+        [Platform::ClaudeCode, Platform::OpenCode, Platform::Codex]
+            .into_iter()
+            .find(|p| p.key() == key)
+    }
+
     /// Get display name for this platform
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -43,7 +50,6 @@ impl fmt::Display for Platform {
 /// Information about a detected directory for a platform
 #[derive(Debug, Clone)]
 pub struct DirectoryInfo {
-    pub path: std::path::PathBuf,
     pub display_path: String,
     pub installed: bool,
 }
@@ -66,7 +72,6 @@ pub fn detect_platforms() -> Vec<DetectionResult> {
         .map(|p| DirectoryInfo {
             installed: claude_code::is_installed_at(&p),
             display_path: format!("~/{}", p.file_name().unwrap_or_default().to_string_lossy()),
-            path: p,
         })
         .collect();
 
@@ -136,8 +141,6 @@ pub fn update(
 }
 
 /// Check if an integration is currently installed (filesystem check)
-/// AIDEV-NOTE: Kept for future use in `bottle status` to verify state matches reality
-#[allow(dead_code)]
 pub fn is_installed(platform: Platform) -> bool {
     match platform {
         Platform::ClaudeCode => claude_code::is_installed(),
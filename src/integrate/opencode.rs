@@ -60,7 +60,6 @@ fn default_config_path() -> PathBuf {
 }
 
 /// Check if the bottle plugin are installed in OpenCode config
-#[allow(dead_code)]
 pub fn is_installed() -> bool {
     let Some(config_path) = get_config_path() else {
         return false;
@@ -1,12 +1,18 @@
 use clap::{Parser, Subcommand, ValueEnum};
 
+mod cache;
 mod commands;
+mod config;
 mod error;
 mod fetch;
 mod install;
 mod integrate;
 mod manifest;
+mod registry;
+mod signature;
+mod source;
 mod ui;
+mod version;
 
 use error::Result;
 
@@ -22,15 +28,37 @@ for users who want a batteries-included experience with the Open Horizon Labs to
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output format (json is supported by status, list, diff and dry-run plans)
+    #[arg(long, global = true, value_enum, default_value = "human")]
+    format: FormatArg,
+
+    /// Use only cached manifests and tool definitions (~/.bottle/cache), never the network
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Registry to fetch bottles and tools from (https URL, file:// directory, or git+<url>[#ref]).
+    /// Defaults to $BOTTLE_REGISTRY, then `registry` in ~/.bottle/config.toml, then GitHub.
+    #[arg(long, global = true, value_name = "URL")]
+    registry: Option<String>,
+
+    /// Skip signature checks on registry documents and trust unsigned bespoke manifests
+    /// for this run
+    #[arg(long, global = true)]
+    allow_unsigned: bool,
+
+    /// Ignore any bottle.json in this directory or above and use the active bottle
+    #[arg(long, global = true)]
+    no_project: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Install a bottle (stable, edge)
+    /// Install a bottle (stable, edge, or the project's bottle.json)
     Install {
-        /// Bottle name to install
-        #[arg(default_value = "stable")]
-        bottle: String,
+        /// Bottle name or shared bottle source (manifest URL, gh:org/repo[/path][#ref],
+        /// git+<url>[//path][#ref]) [default: the project's bottle.json, else stable]
+        bottle: Option<String>,
 
         /// Path to a local manifest file (overrides bottle name lookup)
         #[arg(long, value_name = "PATH")]
@@ -47,30 +75,84 @@ enum Commands {
         /// Reinstall even if already installed
         #[arg(short, long)]
         force: bool,
+
+        /// Roll back everything if any step fails (all-or-nothing)
+        #[arg(long)]
+        strict: bool,
     },
 
-    /// Show current bottle status and installed tools
+    /// Show current bottle status (or the project's) and installed tools
     Status {
         /// Check for available updates
         #[arg(short, long)]
         check_updates: bool,
     },
 
-    /// Update to the latest bottle snapshot
+    /// Check installed tools and integrations against the recorded state
+    Doctor {
+        /// Reinstall or re-register anything missing or at the wrong version
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Update to the latest bottle snapshot (or to the project's edited bottle.json)
     Update {
         /// Skip confirmation prompt
         #[arg(short = 'y', long)]
         yes: bool,
+
+        /// Show what would be done without making changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Roll back everything if any step fails (all-or-nothing)
+        #[arg(long)]
+        strict: bool,
     },
 
     /// Switch to a different bottle
     Switch {
-        /// Bottle name to switch to
+        /// Bottle name or shared bottle source (manifest URL, gh: path or git repository)
         bottle: String,
 
         /// Skip confirmation prompt
         #[arg(short = 'y', long)]
         yes: bool,
+
+        /// Show what would be done without making changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Roll back everything if any step fails (all-or-nothing)
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Show the history of installs, updates and switches
+    History,
+
+    /// Show installer output from the last run, or the latest log for one tool
+    Logs {
+        /// Tool whose most recent log to print
+        tool: Option<String>,
+    },
+
+    /// Roll back to an earlier snapshot from the history
+    Rollback {
+        /// Snapshot index from `bottle history` (default: 1, the previous one) or a bottle version
+        target: Option<String>,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Show what would be done without making changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Roll back everything if any step fails (all-or-nothing)
+        #[arg(long)]
+        strict: bool,
     },
 
     /// Eject from bottle management (keep tools, manage manually)
@@ -80,6 +162,21 @@ enum Commands {
         yes: bool,
     },
 
+    /// Uninstall the active bottle: tools, MCP servers, integrations and state
+    Uninstall {
+        /// Leave installed tools in place (MCP servers and integrations are still removed)
+        #[arg(long)]
+        keep_tools: bool,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Show what would be done without making changes
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Add or remove platform integrations (Claude Code, OpenCode, Codex)
     ///
     /// Multiple Claude directories? Set CLAUDE_CONFIG_DIR to target a specific one:
@@ -111,24 +208,30 @@ enum Commands {
 
     /// Compare bottles or check for updates (curator command)
     Diff {
-        /// First bottle or 'latest' for latest tool versions
+        /// First bottle (curated, bespoke, or manifest path) or 'latest'
         from: String,
 
-        /// Second bottle to compare against
+        /// Second bottle to compare against, or 'latest' for newest published tool versions
         #[arg(default_value = "latest")]
         to: String,
     },
 
-    /// Bump a tool version in a bottle manifest (curator command)
+    /// Bump pinned versions in a bottle manifest (curator command)
     Upgrade {
         /// Bottle to upgrade
         bottle: String,
 
-        /// Tool to upgrade
-        tool: String,
+        /// Tool, custom tool, OpenCode plugin or MCP server to upgrade
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
 
-        /// New version
+        /// New version, or 'latest' for the newest published release
+        #[arg(default_value = "latest")]
         version: String,
+
+        /// Bump every pin that is behind its newest published release
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
     },
 
     /// Validate a bottle manifest (curator command)
@@ -137,14 +240,43 @@ enum Commands {
         bottle: String,
     },
 
+    /// Copy soaked pins from one release channel to a higher one (curator command)
+    Promote {
+        /// Channel to promote from (edge or beta)
+        from: String,
+
+        /// Channel to promote to (beta or stable)
+        to: String,
+
+        /// Promote pins that haven't finished their soak period
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Tag and publish a bottle update (curator command)
     Release {
         /// Bottle to release
         bottle: String,
 
-        /// Release message
+        /// Release message (commit message and changelog entry)
         #[arg(short, long)]
         message: Option<String>,
+
+        /// Sign the manifest and its tool definitions with minisign
+        #[arg(long)]
+        sign: bool,
+
+        /// Minisign secret key (defaults to minisign's own default)
+        #[arg(long, value_name = "PATH", requires = "sign")]
+        key: Option<std::path::PathBuf>,
+
+        /// Show the version bump, changelog, commit, tag and push target without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Commit and tag locally without pushing
+        #[arg(long)]
+        no_push: bool,
     },
 
     /// Create a new bespoke bottle
@@ -152,12 +284,19 @@ enum Commands {
         /// Name for the new bottle
         name: String,
 
-        /// Copy manifest from an existing bottle (curated or bespoke)
+        /// Copy manifest from an existing bottle (curated, bespoke, or a shared bottle's
+        /// URL, gh: path or git repository)
         #[arg(long)]
         from: Option<String>,
     },
 
-    /// Output AGENTS.md snippet for the active bottle
+    /// Save a shared bespoke bottle without installing it
+    Import {
+        /// Manifest URL, gh:org/repo[/path][#ref], or git+<url>[//path][#ref]
+        source: String,
+    },
+
+    /// Output AGENTS.md snippet for the active bottle (or the project's)
     #[command(name = "agents-md")]
     AgentsMd,
 }
@@ -176,6 +315,53 @@ pub enum PlatformArg {
     Codex,
 }
 
+/// Output format selection
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FormatArg {
+    /// Styled terminal output
+    Human,
+    /// Versioned JSON document on stdout
+    Json,
+}
+
+impl FormatArg {
+    /// Convert to the internal OutputFormat type
+    pub fn to_format(self) -> ui::OutputFormat {
+        match self {
+            FormatArg::Human => ui::OutputFormat::Human,
+            FormatArg::Json => ui::OutputFormat::Json,
+        }
+    }
+}
+
+impl Commands {
+    /// Command name used in JSON documents
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Install { .. } => "install",
+            Commands::Status { .. } => "status",
+            Commands::Update { .. } => "update",
+            Commands::Switch { .. } => "switch",
+            Commands::History => "history",
+            Commands::Logs { .. } => "logs",
+            Commands::Rollback { .. } => "rollback",
+            Commands::Doctor { .. } => "doctor",
+            Commands::Eject { .. } => "eject",
+            Commands::Uninstall { .. } => "uninstall",
+            Commands::Integrate { .. } => "integrate",
+            Commands::List => "list",
+            Commands::Diff { .. } => "diff",
+            Commands::Upgrade { .. } => "upgrade",
+            Commands::Validate { .. } => "validate",
+            Commands::Promote { .. } => "promote",
+            Commands::Release { .. } => "release",
+            Commands::Create { .. } => "create",
+            Commands::Import { .. } => "import",
+            Commands::AgentsMd => "agents-md",
+        }
+    }
+}
+
 impl PlatformArg {
     /// Convert to the internal Platform type
     pub fn to_platform(self) -> integrate::Platform {
@@ -188,15 +374,25 @@ impl PlatformArg {
 }
 
 fn main() {
-    if let Err(e) = run() {
+    let cli = Cli::parse();
+    let format = cli.format.to_format();
+    let command = cli.command.name();
+    cache::set_offline(cli.offline);
+    registry::set_override(cli.registry.clone());
+    signature::set_allow_unsigned(cli.allow_unsigned);
+    manifest::project::set_ignore(cli.no_project);
+    install::log::set_operation(command);
+
+    if let Err(e) = run(cli, format) {
+        if format.is_json() {
+            ui::print_json_error(command, &e);
+        }
         ui::print_error(&e);
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
-
+fn run(cli: Cli, format: ui::OutputFormat) -> Result<()> {
     match cli.command {
         Commands::Install {
             bottle,
@@ -204,11 +400,43 @@ fn run() -> Result<()> {
             yes,
             dry_run,
             force,
-        } => commands::install::run(&bottle, manifest.as_deref(), yes, dry_run, force),
-        Commands::Status { check_updates } => commands::status::run(check_updates),
-        Commands::Update { yes } => commands::update::run(yes),
-        Commands::Switch { bottle, yes } => commands::switch::run(&bottle, yes),
+            strict,
+        } => commands::install::run(
+            bottle.as_deref(),
+            manifest.as_deref(),
+            yes,
+            dry_run,
+            force,
+            strict,
+            format,
+        ),
+        Commands::Status { check_updates } => commands::status::run(check_updates, format),
+        Commands::Doctor { fix } => commands::doctor::run(fix, format),
+        Commands::Update {
+            yes,
+            dry_run,
+            strict,
+        } => commands::update::run(yes, dry_run, strict, format),
+        Commands::Switch {
+            bottle,
+            yes,
+            dry_run,
+            strict,
+        } => commands::switch::run(&bottle, yes, dry_run, strict, format),
+        Commands::History => commands::history::run(format),
+        Commands::Logs { tool } => commands::logs::run(tool.as_deref(), format),
+        Commands::Rollback {
+            target,
+            yes,
+            dry_run,
+            strict,
+        } => commands::rollback::run(target.as_deref(), yes, dry_run, strict, format),
         Commands::Eject { yes } => commands::eject::run(yes),
+        Commands::Uninstall {
+            keep_tools,
+            yes,
+            dry_run,
+        } => commands::uninstall::run(keep_tools, yes, dry_run, format),
         Commands::Integrate {
             platform,
             manifest,
@@ -222,18 +450,33 @@ fn run() -> Result<()> {
             remove,
             dry_run,
         ),
-        Commands::List => commands::list::run(),
-        Commands::Diff { from, to } => commands::diff::run(&from, &to),
+        Commands::List => commands::list::run(format),
+        Commands::Diff { from, to } => commands::diff::run(&from, &to, format),
         Commands::Upgrade {
             bottle,
-            tool,
+            name,
             version,
-        } => commands::upgrade::run(&bottle, &tool, &version),
+            all,
+        } => commands::upgrade::run(&bottle, name.as_deref(), &version, all),
         Commands::Validate { bottle } => commands::validate::run(&bottle),
-        Commands::Release { bottle, message } => {
-            commands::release::run(&bottle, message.as_deref())
-        }
+        Commands::Promote { from, to, force } => commands::promote::run(&from, &to, force),
+        Commands::Release {
+            bottle,
+            message,
+            sign,
+            key,
+            dry_run,
+            no_push,
+        } => commands::release::run(
+            &bottle,
+            message.as_deref(),
+            sign,
+            key.as_deref(),
+            dry_run,
+            !no_push,
+        ),
         Commands::Create { name, from } => commands::create::run(&name, from.as_deref()),
+        Commands::Import { source } => commands::import::run(&source),
         Commands::AgentsMd => commands::agents_md::run(),
     }
 }
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -53,9 +54,15 @@ pub struct CustomToolInstall {
     /// npm package name
     #[serde(default)]
     pub npm: Option<String>,
-    /// Direct binary URL (supports {arch} placeholder)
+    /// Direct binary URL (supports {arch} placeholder); may be a .tar.gz or .zip archive
     #[serde(default)]
     pub binary_url: Option<String>,
+    /// Path of the binary inside a binary_url archive (supports placeholders)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<String>,
+    /// SHA-256 of the binary_url download per platform (e.g. "darwin-aarch64" -> hex digest)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sha256: HashMap<String, String>,
 }
 
 /// Custom tool definition for bespoke bottles
@@ -92,4 +99,40 @@ pub struct BottleManifest {
     /// Custom tools to install (bespoke bottles)
     #[serde(default)]
     pub custom_tools: HashMap<String, CustomToolDef>,
+    /// What to do when Homebrew cannot install a pinned version
+    #[serde(default, skip_serializing_if = "BrewPolicy::is_default")]
+    pub brew_policy: BrewPolicy,
+    /// Where the pins were last promoted from (curated channels, see `bottle promote`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promoted_from: Option<Promotion>,
+}
+
+/// Provenance of a promotion between release channels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promotion {
+    /// Channel the pins came from (e.g. "edge")
+    pub channel: String,
+    /// That channel's manifest version at the time
+    pub version: String,
+    /// Registry commit the pins were read at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub promoted_at: DateTime<Utc>,
+}
+
+/// Policy for Homebrew installs that cannot honour the pinned version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrewPolicy {
+    /// Install whatever brew has, with a warning, and record the mismatch
+    #[default]
+    Warn,
+    /// Fail the install
+    Fail,
+}
+
+impl BrewPolicy {
+    fn is_default(&self) -> bool {
+        *self == BrewPolicy::default()
+    }
 }
@@ -0,0 +1,48 @@
+//! Release channels for curated bottles
+//!
+//! Curated pins move edge → beta → stable. A pin lands on edge by hand (`bottle
+//! upgrade edge ...`) and reaches a higher channel through `bottle promote` once it has
+//! soaked on a lower one.
+
+use std::fmt;
+
+/// A curated release channel, ordered from least to most stable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Channel {
+    Edge,
+    Beta,
+    Stable,
+}
+
+impl Channel {
+    /// Every channel, least stable first
+    pub const ALL: [Channel; 3] = [Channel::Edge, Channel::Beta, Channel::Stable];
+
+    /// The channel a curated bottle of this name belongs to
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Edge => "edge",
+            Channel::Beta => "beta",
+            Channel::Stable => "stable",
+        }
+    }
+
+    /// Days a pin must have been on a lower channel before it is promoted to this one
+    pub fn soak_days(self) -> i64 {
+        match self {
+            Channel::Edge => 0,
+            Channel::Beta => 3,
+            Channel::Stable => 7,
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
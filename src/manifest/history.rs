@@ -0,0 +1,105 @@
+use super::state::BottleState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Operation that produced a history entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Install,
+    Update,
+    Switch,
+    Rollback,
+}
+
+impl Operation {
+    /// Command name used in output
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Install => "install",
+            Operation::Update => "update",
+            Operation::Switch => "switch",
+            Operation::Rollback => "rollback",
+        }
+    }
+}
+
+/// Snapshot of the active bottle after an install/update/switch/rollback
+/// AIDEV-NOTE: Stored one JSON object per line in ~/.bottle/history.jsonl and only
+/// ever appended to. Unknown lines are skipped on load so the format can grow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub operation: Operation,
+    pub bottle: String,
+    pub bottle_version: String,
+    /// Tool name -> version
+    pub tools: BTreeMap<String, String>,
+    #[serde(default)]
+    pub integrations: Vec<String>,
+}
+
+impl HistoryEntry {
+    /// Build an entry from the state that was just saved
+    pub fn from_state(operation: Operation, state: &BottleState) -> Self {
+        let mut integrations: Vec<String> = state.integrations.keys().cloned().collect();
+        integrations.sort();
+
+        Self {
+            timestamp: Utc::now(),
+            operation,
+            bottle: state.bottle.clone(),
+            bottle_version: state.bottle_version.clone(),
+            tools: state
+                .tools
+                .iter()
+                .map(|(name, tool)| (name.clone(), tool.version.clone()))
+                .collect(),
+            integrations,
+        }
+    }
+
+    /// Get the path to the history file
+    pub fn path() -> Option<PathBuf> {
+        BottleState::bottle_dir().map(|d| d.join("history.jsonl"))
+    }
+
+    /// Append this entry to the history file
+    pub fn append(&self) -> std::io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Could not determine home directory",
+            )
+        })?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let line = serde_json::to_string(self)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Load all history entries, oldest first
+    pub fn load_all() -> Vec<Self> {
+        let Some(path) = Self::path() else {
+            return Vec::new();
+        };
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Vec::new();
+        };
+
+        contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Curated bottle index published at the registry root (index.json)
+/// AIDEV-NOTE: This is the source of truth for which curated bottles exist. `bottle
+/// release` keeps each entry's version in sync with its manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BottleIndex {
+    pub bottles: Vec<IndexEntry>,
+}
+
+/// One curated bottle in the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub description: String,
+    /// Latest released manifest version
+    pub version: String,
+    /// Release channel (e.g. "stable", "edge")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Deprecation notice; present only for deprecated bottles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+}
+
+impl BottleIndex {
+    /// Path of the index relative to the registry root
+    pub const PATH: &'static str = "index.json";
+
+    /// Look up a bottle by name
+    pub fn get(&self, name: &str) -> Option<&IndexEntry> {
+        self.bottles.iter().find(|b| b.name == name)
+    }
+}
@@ -1,3 +1,7 @@
 pub mod bottle;
+pub mod channel;
+pub mod history;
+pub mod index;
+pub mod project;
 pub mod state;
 pub mod tool;
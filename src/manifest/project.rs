@@ -0,0 +1,129 @@
+//! Project-local bottles
+//!
+//! A repository can pin its own stack in a `bottle.json` (a bottle manifest) at its
+//! root. Commands run anywhere inside it find the file by walking up from the current
+//! directory. Install and update record the versions they installed in a `bottle.lock`
+//! next to it (or next to any other local manifest), meant to be shared with it.
+
+use super::state::BottleState;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Project manifest file name
+pub const MANIFEST_FILE: &str = "bottle.json";
+
+/// Lock file written next to a local manifest
+pub const LOCK_FILE: &str = "bottle.lock";
+
+/// Process-wide --no-project switch
+static IGNORE: AtomicBool = AtomicBool::new(false);
+
+/// Ignore project bottles for this invocation
+pub fn set_ignore(ignore: bool) {
+    IGNORE.store(ignore, Ordering::Relaxed);
+}
+
+/// A directory containing a `bottle.json`
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub root: PathBuf,
+}
+
+impl Project {
+    /// The project containing the current directory, unless --no-project was given
+    pub fn discover() -> Option<Self> {
+        if IGNORE.load(Ordering::Relaxed) {
+            return None;
+        }
+        Self::find_from(&std::env::current_dir().ok()?)
+    }
+
+    /// The nearest directory at or above `dir` with a `bottle.json`
+    pub fn find_from(dir: &Path) -> Option<Self> {
+        dir.ancestors()
+            .find(|d| d.join(MANIFEST_FILE).is_file())
+            .map(|d| Project {
+                root: d.to_path_buf(),
+            })
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.root.join(MANIFEST_FILE)
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        lock_path_for(&self.manifest_path())
+    }
+}
+
+/// The lock file belonging to a local manifest: `bottle.lock` in its directory
+pub fn lock_path_for(manifest_path: &Path) -> PathBuf {
+    manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(LOCK_FILE)
+}
+
+/// Versions installed for a bottle with a local manifest
+/// AIDEV-NOTE: Sorted maps and no timestamps, so the file only changes when a
+/// version does and diffs cleanly in the project's history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub bottle: String,
+    /// Manifest version (`version` in bottle.json)
+    pub version: String,
+    /// Tool name -> installed version
+    pub tools: BTreeMap<String, String>,
+    /// Custom tool name -> installed version
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_tools: BTreeMap<String, String>,
+}
+
+impl Lockfile {
+    /// Load a lock file; None if there is none yet
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the lock file
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        std::fs::write(path, contents)
+    }
+
+    /// Record the installed versions. A tool still at "latest" or a range (its
+    /// release could not be looked up) is recorded as the version it reported.
+    pub fn from_state(state: &BottleState) -> Self {
+        Self {
+            bottle: state.bottle.clone(),
+            version: state.bottle_version.clone(),
+            tools: state
+                .tools
+                .iter()
+                .map(|(name, tool)| {
+                    let version = match tool.reported_version() {
+                        Some(reported) if !version::is_exact(&tool.version) => reported,
+                        _ => &tool.version,
+                    };
+                    (name.clone(), version.to_string())
+                })
+                .collect(),
+            custom_tools: state
+                .custom_tools
+                .iter()
+                .map(|(name, tool)| (name.clone(), tool.version.clone()))
+                .collect(),
+        }
+    }
+}
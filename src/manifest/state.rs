@@ -1,3 +1,4 @@
+use crate::version;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -19,6 +20,10 @@ pub struct BottleState {
     /// AIDEV-NOTE: Optional for backwards compatibility with existing state files
     #[serde(default)]
     pub custom_tools: HashMap<String, CustomToolState>,
+    /// Where a shared bespoke bottle was installed from (URL, gh: path or git
+    /// repository); `bottle update` fetches it from here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// State for a platform integration
@@ -40,6 +45,44 @@ pub struct ToolState {
     pub version: String,
     pub installed_at: DateTime<Utc>,
     pub method: InstallMethod,
+    /// Version reported by the tool's `check` command right after installing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed_version: Option<String>,
+    /// How Homebrew installed the tool (brew installs only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brew: Option<BrewInstall>,
+}
+
+/// Outcome of a Homebrew install
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewInstall {
+    /// Formula actually installed (e.g. "sg@0.9.0")
+    pub formula: String,
+    /// Version Homebrew installed, if it could be determined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether the formula was `brew pin`ned
+    pub pinned: bool,
+    /// Whether the requested version was installed
+    pub exact: bool,
+}
+
+impl ToolState {
+    /// Whether the installed tool is not at the requested version, as reported by
+    /// the binary itself or by Homebrew
+    pub fn version_mismatch(&self) -> bool {
+        self.observed_version
+            .as_deref()
+            .is_some_and(|observed| !version::satisfies(&self.version, observed))
+            || self.brew.as_ref().is_some_and(|b| !b.exact)
+    }
+
+    /// The version the tool or Homebrew reported, if any
+    pub fn reported_version(&self) -> Option<&str> {
+        self.observed_version
+            .as_deref()
+            .or_else(|| self.brew.as_ref().and_then(|b| b.version.as_deref()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +96,10 @@ pub enum Mode {
 #[serde(rename_all = "lowercase")]
 pub enum InstallMethod {
     Cargo,
+    /// cargo-binstall (tracked by cargo, removed with `cargo uninstall`)
+    Binstall,
+    /// Prebuilt release asset written to ~/.local/bin
+    Prebuilt,
     Brew,
     Mcp,
 }
@@ -88,6 +135,11 @@ impl BottleState {
         Self::bottle_path(bottle).map(|d| d.join("state.json"))
     }
 
+    /// Get the path to the record of where a shared bottle was fetched from
+    pub fn source_path(bottle: &str) -> Option<PathBuf> {
+        Self::bottle_path(bottle).map(|d| d.join("source"))
+    }
+
     /// Get the path to a bottle's AGENTS.md snippet
     pub fn snippet_path(bottle: &str) -> Option<PathBuf> {
         Self::bottle_path(bottle).map(|d| d.join("agents-md-snippet"))
@@ -123,6 +175,21 @@ impl BottleState {
         Ok(())
     }
 
+    /// Clear the active bottle (removes the pointer and the legacy symlink)
+    pub fn clear_active() -> std::io::Result<()> {
+        let Some(dir) = Self::bottle_dir() else {
+            return Ok(());
+        };
+        for path in [dir.join("active"), dir.join("state.json")] {
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
     /// Update the legacy state.json symlink to point to the active bottle's state
     #[cfg(unix)]
     fn update_legacy_symlink(bottle: &str) -> std::io::Result<()> {
@@ -206,12 +273,6 @@ impl BottleState {
         std::fs::write(path, content)
     }
 
-    /// Load AGENTS.md snippet for the active bottle
-    pub fn load_snippet() -> Option<String> {
-        let bottle = Self::active_bottle()?;
-        Self::load_snippet_for(&bottle)
-    }
-
     /// Load AGENTS.md snippet for a specific bottle
     pub fn load_snippet_for(bottle: &str) -> Option<String> {
         let path = Self::snippet_path(bottle)?;
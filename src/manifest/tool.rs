@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 /// Tool definition - how to install a specific tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +14,101 @@ pub struct ToolDefinition {
     pub install: HashMap<String, String>,
     pub check: String,
     pub homepage: String,
+    /// Prebuilt release binaries, preferred over building from source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prebuilt: Option<Prebuilt>,
+    /// Other tools or system binaries needed first ("wm", "git", "node>=18")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+}
+
+/// A `requires` entry: another tool in the bottle, or a system binary with an
+/// optional minimum version
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Requirement {
+    pub name: String,
+    pub min_version: Option<String>,
+}
+
+impl Requirement {
+    /// Parse "git" or "node>=18"
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, min_version) = match spec.split_once(">=") {
+            Some((name, version)) => (name.trim(), Some(version.trim())),
+            None => (spec.trim(), None),
+        };
+
+        let valid_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        let valid_version = min_version
+            .is_none_or(|v| !v.is_empty() && v.split('.').all(|p| p.parse::<u64>().is_ok()));
+        if !valid_name || !valid_version {
+            return None;
+        }
+
+        Some(Requirement {
+            name: name.to_string(),
+            min_version: min_version.map(|v| v.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.min_version {
+            Some(version) => write!(f, "{} >= {}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Release assets for a binary tool, keyed by Rust target triple
+/// (e.g. "aarch64-apple-darwin", "x86_64-unknown-linux-gnu")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prebuilt {
+    /// Download URL; {version}, {target} and {asset} are expanded
+    pub url: String,
+    /// Asset file name per target; {version} and {target} are expanded
+    pub assets: HashMap<String, String>,
+    /// Path of the binary inside archive assets; {version} and {target} are expanded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<String>,
+    /// sha256 digests per version, then per target
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sha256: HashMap<String, HashMap<String, String>>,
+}
+
+impl ToolDefinition {
+    /// Parsed `requires` entries (malformed ones are reported by `bottle validate`)
+    pub fn requirements(&self) -> Vec<Requirement> {
+        self.requires
+            .iter()
+            .filter_map(|spec| Requirement::parse(spec))
+            .collect()
+    }
+
+    /// Homebrew tap and formula, parsed from the `brew` install command
+    /// (e.g. "brew tap org/homebrew-tap && brew install sg"). Falls back to the package name.
+    pub fn brew_formula(&self) -> (Option<String>, String) {
+        let mut tap = None;
+        let mut formula = None;
+        for step in self
+            .install
+            .get("brew")
+            .into_iter()
+            .flat_map(|c| c.split("&&"))
+        {
+            let words: Vec<&str> = step.split_whitespace().collect();
+            match words.as_slice() {
+                ["brew", "tap", name, ..] => tap = Some(name.to_string()),
+                ["brew", "install", name, ..] => formula = Some(name.to_string()),
+                _ => {}
+            }
+        }
+        (tap, formula.unwrap_or_else(|| self.package.clone()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,252 @@
+//! Where bottles, tool definitions and Codex skills are fetched from
+//!
+//! The registry is a directory tree laid out like this repository (`bottles/`,
+//! `tools/`, `codex-skill/`). It can be served over HTTPS, read from a local
+//! directory, or kept as a git checkout under ~/.bottle/registries.
+
+use crate::config::Config;
+use crate::error::{BottleError, Result};
+use crate::{cache, ui};
+use once_cell::sync::OnceCell;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The public registry (this repository on GitHub)
+pub const DEFAULT_REGISTRY: &str =
+    "https://raw.githubusercontent.com/open-horizon-labs/bottle/master";
+
+/// Environment variable that overrides the configured registry
+pub const REGISTRY_ENV: &str = "BOTTLE_REGISTRY";
+
+/// Registry given with --registry (highest precedence)
+static FLAG_OVERRIDE: OnceCell<String> = OnceCell::new();
+
+/// Resolved registry, computed once per process
+static CURRENT: OnceCell<Registry> = OnceCell::new();
+
+/// A registry location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Registry {
+    /// Base URL (https, or http on loopback for local test servers)
+    Http(String),
+    /// Local directory (file://)
+    File(PathBuf),
+    /// Git repository cloned into ~/.bottle/registries (git+<url>[#ref])
+    Git {
+        url: String,
+        reference: Option<String>,
+    },
+}
+
+/// Set the registry from the --registry flag
+pub fn set_override(spec: Option<String>) {
+    if let Some(spec) = spec {
+        let _ = FLAG_OVERRIDE.set(spec);
+    }
+}
+
+/// The registry to use: --registry, then $BOTTLE_REGISTRY, then config.toml, then the default
+pub fn current() -> Result<&'static Registry> {
+    CURRENT.get_or_try_init(|| {
+        let spec = match FLAG_OVERRIDE.get() {
+            Some(spec) => spec.clone(),
+            None => match std::env::var(REGISTRY_ENV) {
+                Ok(spec) if !spec.trim().is_empty() => spec,
+                _ => Config::load()?
+                    .registry
+                    .unwrap_or_else(|| DEFAULT_REGISTRY.to_string()),
+            },
+        };
+        Registry::parse(&spec)
+    })
+}
+
+impl Registry {
+    /// Parse a registry spec
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+
+        if let Some(git) = spec.strip_prefix("git+") {
+            let (url, reference) = match git.split_once('#') {
+                Some((url, reference)) => (url, Some(reference.to_string())),
+                None => (git, None),
+            };
+            return Ok(Registry::Git {
+                url: url.to_string(),
+                reference,
+            });
+        }
+
+        if let Some(path) = spec.strip_prefix("file://") {
+            return Ok(Registry::File(PathBuf::from(path)));
+        }
+
+        if spec.starts_with("https://") || is_loopback_http(spec) {
+            return Ok(Registry::Http(spec.trim_end_matches('/').to_string()));
+        }
+
+        Err(BottleError::Other(format!(
+            "Unsupported registry '{}'. Use an https:// URL, a file:// directory, or git+<url>[#ref].",
+            spec
+        )))
+    }
+
+    /// Whether this is the public registry
+    pub fn is_default(&self) -> bool {
+        matches!(self, Registry::Http(base) if base == DEFAULT_REGISTRY)
+    }
+
+    /// Cache namespace for documents from this registry
+    /// AIDEV-NOTE: The default registry caches at the top of ~/.bottle/cache; mirrors get
+    /// their own subdirectory so switching registries never serves the wrong document.
+    pub fn cache_prefix(&self) -> Option<String> {
+        match self {
+            Registry::Http(base) if !self.is_default() => {
+                Some(format!("registries/{}", slug(base)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Read a document from a local (file or git) registry; None if it does not exist.
+    /// HTTP registries are handled by `fetch` so they go through the cache.
+    pub fn read_local(&self, path: &str) -> Result<Option<String>> {
+        let root = match self {
+            Registry::File(root) => root.clone(),
+            Registry::Git { url, reference } => git_checkout(url, reference.as_deref())?,
+            Registry::Http(_) => {
+                return Err(BottleError::Other(
+                    "HTTP registries are not local".to_string(),
+                ))
+            }
+        };
+
+        match std::fs::read_to_string(root.join(path)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Registry::Http(base) => write!(f, "{}", base),
+            Registry::File(path) => write!(f, "file://{}", path.display()),
+            Registry::Git {
+                url,
+                reference: Some(reference),
+            } => write!(f, "git+{}#{}", url, reference),
+            Registry::Git { url, .. } => write!(f, "git+{}", url),
+        }
+    }
+}
+
+/// Plain http is only allowed for local stand-in servers
+pub fn is_loopback_http(spec: &str) -> bool {
+    let Some(rest) = spec.strip_prefix("http://") else {
+        return false;
+    };
+    let host = if rest.starts_with("[::1]") {
+        "[::1]"
+    } else {
+        rest.split(['/', ':']).next().unwrap_or("")
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Filesystem-safe name for a registry location
+fn slug(s: &str) -> String {
+    let without_scheme = s.split_once("://").map(|(_, rest)| rest).unwrap_or(s);
+    let slug: String = without_scheme
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.trim_matches('-').to_string()
+}
+
+/// Clone or refresh a git registry (once per process) and return its checkout path
+fn git_checkout(url: &str, reference: Option<&str>) -> Result<PathBuf> {
+    static CHECKOUT: OnceCell<PathBuf> = OnceCell::new();
+
+    CHECKOUT
+        .get_or_try_init(|| {
+            let dir = dirs::home_dir()
+                .map(|h| h.join(".bottle").join("registries").join(slug(url)))
+                .ok_or_else(|| BottleError::Other("Could not determine home directory".into()))?;
+
+            if cache::is_offline() {
+                return if dir.exists() {
+                    ui::eprint_warning(&format!(
+                        "Using git registry checkout {} without refreshing (offline)",
+                        dir.display()
+                    ));
+                    Ok(dir)
+                } else {
+                    Err(BottleError::NotCached(format!("git registry {}", url)))
+                };
+            }
+
+            let refresh = if dir.exists() {
+                let reference = reference.unwrap_or("HEAD");
+                run_git(&[
+                    "-C",
+                    path_str(&dir)?,
+                    "fetch",
+                    "--depth",
+                    "1",
+                    "origin",
+                    reference,
+                ])
+                .and_then(|()| run_git(&["-C", path_str(&dir)?, "reset", "--hard", "FETCH_HEAD"]))
+            } else {
+                if let Some(parent) = dir.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut args = vec!["clone", "--depth", "1"];
+                if let Some(reference) = reference {
+                    args.extend(["--branch", reference]);
+                }
+                args.extend([url, path_str(&dir)?]);
+                run_git(&args)
+            };
+
+            match refresh {
+                Ok(()) => Ok(dir),
+                // Keep working from the last good checkout if the remote is unreachable
+                Err(e) if dir.join(".git").exists() => {
+                    ui::eprint_warning(&format!(
+                        "Could not refresh git registry ({}), using existing checkout",
+                        e
+                    ));
+                    Ok(dir)
+                }
+                Err(e) => Err(e),
+            }
+        })
+        .cloned()
+}
+
+pub fn path_str(path: &std::path::Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| BottleError::Other(format!("Non-UTF-8 path: {}", path.display())))
+}
+
+/// Run git quietly, turning a non-zero exit into an error
+pub fn run_git(args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| BottleError::Other(format!("Failed to run git: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(BottleError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
@@ -0,0 +1,184 @@
+//! Detached minisign signatures for manifests and tool definitions
+//!
+//! Every `manifest.json` and `tools/*.json` in the registry has a `.minisig` next to
+//! it. Signatures are checked against the pinned keys below plus any `trusted_keys` in
+//! ~/.bottle/config.toml (for organizations running a mirror).
+
+use crate::config::Config;
+use crate::error::{BottleError, Result};
+use crate::registry;
+use crate::ui;
+use minisign_verify::{PublicKey, Signature};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// Extension of detached signature files
+pub const SIGNATURE_EXT: &str = "minisig";
+
+/// Minisign public keys trusted for the public registry
+/// AIDEV-NOTE: The project release key goes here (the `RW...` line of minisign.pub),
+/// and every curated manifest and tool definition is signed with it
+/// (scripts/sign-registry.sh). Checks on the public registry fail closed once a key is
+/// pinned; until then its documents are accepted with a one-time warning, so pin the
+/// key in the same change that commits the `.minisig` files.
+pub const TRUSTED_KEYS: &[&str] = &[];
+
+/// Process-wide --allow-unsigned switch
+static ALLOW_UNSIGNED: AtomicBool = AtomicBool::new(false);
+
+/// Accept unsigned manifests and tool definitions for this invocation
+pub fn set_allow_unsigned(allow: bool) {
+    ALLOW_UNSIGNED.store(allow, Ordering::Relaxed);
+}
+
+/// Path of the detached signature for a document
+pub fn signature_path(path: &str) -> String {
+    format!("{}.{}", path, SIGNATURE_EXT)
+}
+
+/// All trusted keys: pinned plus configured
+fn trusted_keys(config: &Config) -> Vec<String> {
+    TRUSTED_KEYS
+        .iter()
+        .map(|k| k.to_string())
+        .chain(config.trusted_keys.iter().cloned())
+        .collect()
+}
+
+/// Check `body` against `signature` with any trusted key
+fn verify_with_keys(label: &str, body: &str, signature: &str, keys: &[String]) -> Result<()> {
+    let signature = Signature::decode(signature).map_err(|e| {
+        BottleError::SignatureError(format!("{}: malformed signature ({})", label, e))
+    })?;
+
+    for key in keys {
+        let public_key = PublicKey::from_base64(key.trim()).map_err(|e| {
+            BottleError::SignatureError(format!("invalid trusted key '{}': {}", key, e))
+        })?;
+        if public_key
+            .verify(body.as_bytes(), &signature, false)
+            .is_ok()
+        {
+            return Ok(());
+        }
+    }
+
+    Err(BottleError::SignatureError(format!(
+        "{} is not signed by a trusted key",
+        label
+    )))
+}
+
+/// Verify a manifest or tool definition fetched from the registry.
+/// The public registry is checked once `TRUSTED_KEYS` pins a key, and a mirror once
+/// `trusted_keys` names its key; both are accepted with a warning until then.
+/// --allow-unsigned skips the check everywhere.
+pub fn verify_registry_document(path: &str, body: &str, signature: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let is_default = registry::current()?.is_default();
+    let unchecked = if is_default {
+        TRUSTED_KEYS.is_empty()
+    } else {
+        config.trusted_keys.is_empty()
+    };
+    let allow_unsigned = ALLOW_UNSIGNED.load(Ordering::Relaxed) || unchecked;
+
+    let keys = trusted_keys(&config);
+    if allow_unsigned && (keys.is_empty() || signature.is_none()) {
+        static WARN: Once = Once::new();
+        WARN.call_once(|| {
+            ui::eprint_warning(if unchecked && is_default {
+                "No signing key is pinned for the public registry yet; its signatures are not checked"
+            } else if unchecked {
+                "No trusted_keys configured for this registry; its signatures are not checked"
+            } else {
+                "--allow-unsigned: registry signatures are not checked"
+            })
+        });
+    }
+
+    verify_document(
+        path,
+        body,
+        signature,
+        &keys,
+        allow_unsigned,
+        "Re-run with --allow-unsigned to skip signature checks.",
+    )
+}
+
+/// Verify a bespoke manifest loaded from disk.
+/// Signed manifests must verify; unsigned ones need --allow-unsigned or an
+/// `unsigned_bottles` entry in config.toml.
+pub fn verify_bespoke_manifest(
+    name: &str,
+    manifest_path: &std::path::Path,
+    body: &str,
+) -> Result<()> {
+    let mut sig_path = manifest_path.as_os_str().to_owned();
+    sig_path.push(format!(".{}", SIGNATURE_EXT));
+    let signature = std::fs::read_to_string(&sig_path).ok();
+
+    verify_bespoke_document(
+        name,
+        &manifest_path.display().to_string(),
+        body,
+        signature.as_deref(),
+    )
+}
+
+/// Verify a bespoke manifest from anywhere (`label` says where, for errors) against
+/// its detached signature, with the same rules as `verify_bespoke_manifest`
+pub fn verify_bespoke_document(
+    name: &str,
+    label: &str,
+    body: &str,
+    signature: Option<&str>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let allow_unsigned =
+        ALLOW_UNSIGNED.load(Ordering::Relaxed) || config.unsigned_bottles.iter().any(|b| b == name);
+
+    verify_document(
+        label,
+        body,
+        signature,
+        &trusted_keys(&config),
+        allow_unsigned,
+        &format!(
+            "Re-run with --allow-unsigned, or add \"{}\" to `unsigned_bottles` in \
+             ~/.bottle/config.toml to always trust it.",
+            name
+        ),
+    )
+}
+
+/// Check a document against its signature. When the caller accepts unsigned
+/// documents, a signature that can't be checked (no trusted keys) counts as none; a
+/// signature that fails against a trusted key is always rejected.
+/// AIDEV-NOTE: Registry and bespoke documents share these rules so neither is ever
+/// stricter about a signed document than about an unsigned one.
+fn verify_document(
+    label: &str,
+    body: &str,
+    signature: Option<&str>,
+    keys: &[String],
+    allow_unsigned: bool,
+    hint: &str,
+) -> Result<()> {
+    match signature {
+        Some(signature) if !keys.is_empty() => verify_with_keys(label, body, signature, keys),
+        _ if allow_unsigned => Ok(()),
+        Some(_) => Err(BottleError::SignatureError(format!(
+            "{} is signed, but no trusted keys are configured to check it. Add the signer's \
+             public key to `trusted_keys` in ~/.bottle/config.toml. {}",
+            label, hint
+        ))),
+        None => Err(BottleError::SignatureError(format!(
+            "{} has no signature ({}). {}",
+            label,
+            signature_path(label),
+            hint
+        ))),
+    }
+}
@@ -0,0 +1,366 @@
+//! Where shared bespoke bottles are fetched from
+//!
+//! A team shares a bespoke bottle as a manifest URL, a path in a GitHub repository
+//! (`gh:org/repo/path`) or a git repository. `install`, `switch`, `create --from` and
+//! `import` accept any of these in place of a bottle name; the manifest is saved
+//! under ~/.bottle/bottles/<name>, and `update` fetches it again from its source.
+
+use crate::cache;
+use crate::error::{BottleError, Result};
+use crate::fetch::fetch_url;
+use crate::registry::{is_loopback_http, path_str, run_git};
+use crate::signature::signature_path;
+use std::fmt;
+use std::fs;
+
+/// Manifest file looked up in a repository directory
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Raw file host for gh: sources
+const GITHUB_RAW: &str = "https://raw.githubusercontent.com";
+
+/// A shared bottle's location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Manifest URL (https, file://, or http on loopback for local test servers)
+    Url(String),
+    /// Manifest in a GitHub repository (gh:org/repo[/path][#ref])
+    GitHub {
+        repo: String,
+        path: String,
+        reference: Option<String>,
+    },
+    /// Manifest in a git repository (git+<url>[//path][#ref], or a plain git URL)
+    Git {
+        url: String,
+        path: String,
+        reference: Option<String>,
+    },
+}
+
+/// A fetched manifest with its detached signature, if it has one
+#[derive(Debug)]
+pub struct Fetched {
+    pub body: String,
+    pub signature: Option<String>,
+}
+
+impl Source {
+    /// Parse a source. Returns None for anything that isn't one (a bottle name).
+    pub fn parse(spec: &str) -> Result<Option<Self>> {
+        let spec = spec.trim();
+
+        if let Some(rest) = spec.strip_prefix("gh:") {
+            let (rest, reference) = split_reference(rest);
+            let mut parts = rest.trim_matches('/').splitn(3, '/');
+            let (Some(org), Some(repo)) = (parts.next(), parts.next()) else {
+                return Err(BottleError::Other(format!(
+                    "'{}' is not a GitHub source. Use gh:org/repo[/path][#ref].",
+                    spec
+                )));
+            };
+            return Ok(Some(Source::GitHub {
+                repo: format!("{}/{}", org, repo),
+                path: manifest_file(parts.next().unwrap_or("")),
+                reference,
+            }));
+        }
+
+        let git = match spec.strip_prefix("git+") {
+            Some(url) => Some(url),
+            None if is_git_url(spec) => Some(spec),
+            None => None,
+        };
+        if let Some(git) = git {
+            let (git, reference) = split_reference(git);
+            let (url, path) = split_path(git);
+            return Ok(Some(Source::Git {
+                url: url.to_string(),
+                path: manifest_file(path),
+                reference,
+            }));
+        }
+
+        if spec.starts_with("https://") || spec.starts_with("file://") || is_loopback_http(spec) {
+            return Ok(Some(Source::Url(spec.to_string())));
+        }
+
+        if spec.contains("://") {
+            return Err(BottleError::Other(format!(
+                "Unsupported bottle source '{}'. Use an https:// manifest URL, \
+                 gh:org/repo[/path][#ref], or git+<url>[//path][#ref].",
+                spec
+            )));
+        }
+
+        Ok(None)
+    }
+
+    /// Fetch the manifest and its signature
+    pub fn fetch(&self) -> Result<Fetched> {
+        match self {
+            Source::Url(url) => fetch_document(url),
+            Source::GitHub {
+                repo,
+                path,
+                reference,
+            } => fetch_document(&format!(
+                "{}/{}/{}/{}",
+                GITHUB_RAW,
+                repo,
+                reference.as_deref().unwrap_or("HEAD"),
+                path
+            )),
+            Source::Git {
+                url,
+                path,
+                reference,
+            } => fetch_from_git(url, path, reference.as_deref()),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, location, path, reference) = match self {
+            Source::Url(url) => return write!(f, "{}", url),
+            Source::GitHub {
+                repo,
+                path,
+                reference,
+            } => ("gh:", repo, path, reference),
+            Source::Git {
+                url,
+                path,
+                reference,
+            } => ("git+", url, path, reference),
+        };
+        write!(f, "{}{}", prefix, location)?;
+        // gh: paths follow the repository; git paths need the `//` separator. A
+        // directory's manifest.json is implied.
+        let path = path
+            .strip_suffix(&format!("/{}", MANIFEST_FILE))
+            .unwrap_or(path);
+        match (prefix, path) {
+            (_, MANIFEST_FILE) => {}
+            ("gh:", path) => write!(f, "/{}", path)?,
+            (_, path) => write!(f, "//{}", path)?,
+        }
+        if let Some(reference) = reference {
+            write!(f, "#{}", reference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split off a trailing `#ref`
+fn split_reference(spec: &str) -> (&str, Option<String>) {
+    match spec.rsplit_once('#') {
+        Some((spec, reference)) if !reference.is_empty() => (spec, Some(reference.to_string())),
+        Some((spec, _)) => (spec, None),
+        None => (spec, None),
+    }
+}
+
+/// Split a git URL from the `//path` inside the repository
+fn split_path(url: &str) -> (&str, &str) {
+    let start = url.find("://").map(|i| i + 3).unwrap_or(0);
+    match url[start..].find("//") {
+        Some(i) => (&url[..start + i], &url[start + i + 2..]),
+        None => (url, ""),
+    }
+}
+
+/// Git URLs that need no `git+` prefix: ssh forms, and anything ending in `.git`
+fn is_git_url(spec: &str) -> bool {
+    let (url, _) = split_path(split_reference(spec).0);
+    spec.starts_with("git@")
+        || spec.starts_with("ssh://")
+        || spec.starts_with("git://")
+        || url.ends_with(".git")
+}
+
+/// The manifest a path names: a `.json` file, or a directory's manifest.json
+fn manifest_file(path: &str) -> String {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        MANIFEST_FILE.to_string()
+    } else if path.ends_with(".json") {
+        path.to_string()
+    } else {
+        format!("{}/{}", path, MANIFEST_FILE)
+    }
+}
+
+/// Fetch a manifest and its signature from a URL
+fn fetch_document(url: &str) -> Result<Fetched> {
+    let not_found = || BottleError::BottleNotFound(format!("no manifest at {}", url));
+
+    if let Some(path) = url.strip_prefix("file://") {
+        let body = fs::read_to_string(path).map_err(|_| not_found())?;
+        let signature = fs::read_to_string(signature_path(path)).ok();
+        return Ok(Fetched { body, signature });
+    }
+
+    let body = fetch_url(url)?.ok_or_else(not_found)?;
+    let signature = fetch_url(&signature_path(url))?;
+    Ok(Fetched { body, signature })
+}
+
+/// Read a manifest and its signature from a shallow clone of a git repository
+/// AIDEV-NOTE: The clone is thrown away; the saved bespoke bottle is the copy that is
+/// kept, so there is no checkout to refresh like a git registry's. It goes in a fresh
+/// private temporary directory, never a predictable path another user could plant.
+fn fetch_from_git(url: &str, path: &str, reference: Option<&str>) -> Result<Fetched> {
+    if cache::is_offline() {
+        return Err(BottleError::Other(format!(
+            "Cannot clone {} in offline mode",
+            url
+        )));
+    }
+
+    let temp = tempfile::Builder::new()
+        .prefix("bottle-source-")
+        .tempdir()?;
+    let dir = temp.path().join("repo");
+
+    let mut args = vec!["clone", "--quiet", "--depth", "1"];
+    if let Some(reference) = reference {
+        args.extend(["--branch", reference]);
+    }
+    args.extend([url, path_str(&dir)?]);
+
+    run_git(&args)?;
+    let manifest = dir.join(path);
+    let body = fs::read_to_string(&manifest)
+        .map_err(|_| BottleError::BottleNotFound(format!("no {} in {}", path, url)))?;
+    let signature = fs::read_to_string(signature_path(path_str(&manifest)?)).ok();
+    Ok(Fetched { body, signature })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(spec: &str) -> Source {
+        Source::parse(spec).unwrap().expect("a source")
+    }
+
+    /// The saved `source` file is the Display form; it must parse back to the same source
+    fn assert_round_trip(spec: &str, expected: Source, display: &str) {
+        let source = parse(spec);
+        assert_eq!(source, expected);
+        assert_eq!(source.to_string(), display);
+        assert_eq!(parse(display), expected);
+        assert_eq!(parse(display).to_string(), display);
+    }
+
+    fn git(url: &str, path: &str, reference: Option<&str>) -> Source {
+        Source::Git {
+            url: url.to_string(),
+            path: path.to_string(),
+            reference: reference.map(str::to_string),
+        }
+    }
+
+    fn github(repo: &str, path: &str, reference: Option<&str>) -> Source {
+        Source::GitHub {
+            repo: repo.to_string(),
+            path: path.to_string(),
+            reference: reference.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn https_url() {
+        let url = "https://example.com/bottles/team/manifest.json";
+        assert_round_trip(url, Source::Url(url.to_string()), url);
+    }
+
+    #[test]
+    fn github_repository_root() {
+        assert_round_trip(
+            "gh:acme/env",
+            github("acme/env", "manifest.json", None),
+            "gh:acme/env",
+        );
+    }
+
+    #[test]
+    fn github_path_and_reference() {
+        assert_round_trip(
+            "gh:acme/env/bottles/team#v1.2",
+            github("acme/env", "bottles/team/manifest.json", Some("v1.2")),
+            "gh:acme/env/bottles/team#v1.2",
+        );
+        assert_round_trip(
+            "gh:acme/env/bottles/team/manifest.json",
+            github("acme/env", "bottles/team/manifest.json", None),
+            "gh:acme/env/bottles/team",
+        );
+        assert_round_trip(
+            "gh:acme/env/team.json",
+            github("acme/env", "team.json", None),
+            "gh:acme/env/team.json",
+        );
+    }
+
+    #[test]
+    fn git_plus_url_with_path_and_reference() {
+        assert_round_trip(
+            "git+https://git.acme.dev/env.git//bottles/team#main",
+            git(
+                "https://git.acme.dev/env.git",
+                "bottles/team/manifest.json",
+                Some("main"),
+            ),
+            "git+https://git.acme.dev/env.git//bottles/team#main",
+        );
+        assert_round_trip(
+            "git+https://git.acme.dev/env",
+            git("https://git.acme.dev/env", "manifest.json", None),
+            "git+https://git.acme.dev/env",
+        );
+    }
+
+    #[test]
+    fn scp_style_git_url() {
+        assert_round_trip(
+            "git@github.com:acme/env.git",
+            git("git@github.com:acme/env.git", "manifest.json", None),
+            "git+git@github.com:acme/env.git",
+        );
+        assert_round_trip(
+            "git@github.com:acme/env.git//bottles/team#v2",
+            git(
+                "git@github.com:acme/env.git",
+                "bottles/team/manifest.json",
+                Some("v2"),
+            ),
+            "git+git@github.com:acme/env.git//bottles/team#v2",
+        );
+    }
+
+    #[test]
+    fn dot_git_url() {
+        assert_round_trip(
+            "https://github.com/acme/env.git",
+            git("https://github.com/acme/env.git", "manifest.json", None),
+            "git+https://github.com/acme/env.git",
+        );
+        assert_round_trip(
+            "ssh://git@git.acme.dev/env.git//team.json",
+            git("ssh://git@git.acme.dev/env.git", "team.json", None),
+            "git+ssh://git@git.acme.dev/env.git//team.json",
+        );
+    }
+
+    #[test]
+    fn bottle_names_and_other_schemes() {
+        assert_eq!(Source::parse("stable").unwrap(), None);
+        assert_eq!(Source::parse("my-team").unwrap(), None);
+        assert!(Source::parse("ftp://example.com/manifest.json").is_err());
+        assert!(Source::parse("http://example.com/manifest.json").is_err());
+        assert!(Source::parse("gh:acme").is_err());
+    }
+}
@@ -1,8 +1,65 @@
 use console::style;
 use dialoguer::{theme::ColorfulTheme, Confirm};
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::sync::Mutex;
 
-use crate::error::BottleError;
+use crate::error::{BottleError, Result};
+
+/// Version of the JSON document layout emitted by `--format json`.
+/// AIDEV-NOTE: Bump when removing or renaming fields; adding fields is compatible.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// How command results are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Styled terminal output
+    #[default]
+    Human,
+    /// One versioned JSON document on stdout
+    Json,
+}
+
+impl OutputFormat {
+    pub fn is_json(self) -> bool {
+        self == OutputFormat::Json
+    }
+}
+
+/// Envelope shared by every JSON document
+#[derive(Serialize)]
+struct JsonDocument<'a, T: Serialize> {
+    schema_version: u32,
+    command: &'a str,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+/// Print a command result as a versioned JSON document
+pub fn print_json<T: Serialize>(command: &str, data: &T) -> Result<()> {
+    let document = JsonDocument {
+        schema_version: JSON_SCHEMA_VERSION,
+        command,
+        data,
+    };
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+/// Print an error as a JSON document (for `--format json`)
+pub fn print_json_error(command: &str, error: &BottleError) {
+    #[derive(Serialize)]
+    struct ErrorData {
+        error: String,
+    }
+
+    let data = ErrorData {
+        error: error.to_string(),
+    };
+    if print_json(command, &data).is_err() {
+        eprintln!("Failed to serialize error document");
+    }
+}
 
 /// Print an error message
 pub fn print_error(error: &BottleError) {
@@ -11,17 +68,51 @@ pub fn print_error(error: &BottleError) {
 
 /// Print a success message
 pub fn print_success(message: &str) {
-    println!("{} {}", style("✓").green().bold(), message);
+    print_line(format!("{} {}", style("✓").green().bold(), message), false);
 }
 
 /// Print an info message
 pub fn print_info(message: &str) {
-    println!("{} {}", style("•").blue(), message);
+    print_line(format!("{} {}", style("•").blue(), message), false);
 }
 
 /// Print a warning message
 pub fn print_warning(message: &str) {
-    println!("{} {}", style("!").yellow().bold(), message);
+    print_line(format!("{} {}", style("!").yellow().bold(), message), false);
+}
+
+/// Print a warning to stderr (keeps JSON stdout clean)
+pub fn eprint_warning(message: &str) {
+    print_line(format!("{} {}", style("!").yellow().bold(), message), true);
+}
+
+/// Progress bars of the running `progress_group`, if any
+static PROGRESS: Mutex<Option<MultiProgress>> = Mutex::new(None);
+
+/// Start a group of progress bars (e.g. parallel installs). Until
+/// `end_progress_group`, messages from any thread are printed above the bars
+/// instead of through them.
+pub fn progress_group() -> MultiProgress {
+    let progress = MultiProgress::new();
+    *PROGRESS.lock().unwrap_or_else(|e| e.into_inner()) = Some(progress.clone());
+    progress
+}
+
+/// End the group started by `progress_group`
+pub fn end_progress_group() {
+    PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).take();
+}
+
+/// Print a message line, above the active progress bars if they are drawn
+fn print_line(line: String, stderr: bool) {
+    let progress = PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match progress {
+        Some(progress) if !progress.is_hidden() => {
+            let _ = progress.println(line);
+        }
+        _ if stderr => eprintln!("{}", line),
+        _ => println!("{}", line),
+    }
 }
 
 /// Print a header
@@ -0,0 +1,185 @@
+//! Version strings and constraints
+//!
+//! Manifests pin each tool to an exact version, `latest`, or a semver range (`^0.3`,
+//! `~0.9.1`, `>=0.2, <0.4`). Every comparison in bottle goes through here, on the
+//! semver crate, so prereleases order before their release (`1.0.0-rc.1` < `1.0.0`).
+
+use semver::{Version, VersionReq};
+use std::cmp::Ordering;
+
+/// What a manifest asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Spec {
+    Latest,
+    Exact(Version),
+    Range(VersionReq),
+}
+
+impl Spec {
+    /// Parse a manifest version. A full `x.y.z` (optionally `v`-prefixed) is exact;
+    /// anything else semver accepts is a range, so `0.3` means `^0.3` as in Cargo.toml.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec == "latest" {
+            return Some(Spec::Latest);
+        }
+        if let Ok(version) = Version::parse(spec.trim_start_matches('v')) {
+            return Some(Spec::Exact(version));
+        }
+        VersionReq::parse(spec).ok().map(Spec::Range)
+    }
+
+    /// Whether `version` (as reported by a tool or registry) satisfies this spec
+    pub fn matches(&self, version: &str) -> bool {
+        match (self, parse(version)) {
+            (Spec::Latest, _) => true,
+            (Spec::Exact(exact), Some(version)) => *exact == version,
+            (Spec::Range(req), Some(version)) => req.matches(&version),
+            (_, None) => false,
+        }
+    }
+}
+
+/// Parse a version leniently: `v1.2.3`, `1.2` and `18` (as printed by `--version`
+/// or written as a minimum) are read as `1.2.3`, `1.2.0` and `18.0.0`
+pub fn parse(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    if let Ok(parsed) = Version::parse(version) {
+        return Some(parsed);
+    }
+
+    // Pad a short core ("1.2-beta" -> "1.2.0-beta")
+    let split = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, suffix) = version.split_at(split);
+    let parts = core.split('.').count();
+    if parts >= 3 || core.split('.').any(|p| p.parse::<u64>().is_err()) {
+        return None;
+    }
+    let padded = format!("{}{}{}", core, ".0".repeat(3 - parts), suffix);
+    Version::parse(&padded).ok()
+}
+
+/// Whether a manifest version names exactly one version
+pub fn is_exact(spec: &str) -> bool {
+    matches!(Spec::parse(spec), Some(Spec::Exact(_)))
+}
+
+/// Whether `version` satisfies a manifest version ("latest" accepts anything).
+/// Strings that don't parse only match themselves.
+pub fn satisfies(spec: &str, version: &str) -> bool {
+    match Spec::parse(spec) {
+        Some(parsed) => parsed.matches(version),
+        None => spec == version,
+    }
+}
+
+/// Order two versions; None if either isn't one
+pub fn compare(a: &str, b: &str) -> Option<Ordering> {
+    Some(parse(a)?.cmp(&parse(b)?))
+}
+
+/// Whether `found` is at least `min` ("18.19.0" is at least "18", "18.0.0-rc.1" is not)
+pub fn at_least(found: &str, min: &str) -> bool {
+    compare(found, min).is_some_and(|o| o != Ordering::Less)
+}
+
+/// The newest of `versions` that satisfies a range. Prereleases are only chosen when
+/// the range names one, as with Cargo.
+pub fn newest_matching<'a>(req: &VersionReq, versions: &'a [String]) -> Option<&'a String> {
+    versions
+        .iter()
+        .filter_map(|v| Some((parse(v)?, v)))
+        .filter(|(parsed, _)| req.matches(parsed))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(list: &[&str]) -> Vec<String> {
+        list.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn caret_range() {
+        assert!(satisfies("^0.3", "0.3.0"));
+        assert!(satisfies("^0.3", "0.3.9"));
+        assert!(!satisfies("^0.3", "0.4.0"));
+        assert!(!satisfies("^0.3", "0.2.9"));
+    }
+
+    #[test]
+    fn bare_minor_is_caret() {
+        assert_eq!(Spec::parse("0.3"), Spec::parse("^0.3"));
+        assert!(satisfies("0.3", "0.3.5"));
+        assert!(!satisfies("0.3", "0.4.0"));
+    }
+
+    #[test]
+    fn tilde_range() {
+        assert!(satisfies("~0.9.1", "0.9.1"));
+        assert!(satisfies("~0.9.1", "0.9.7"));
+        assert!(!satisfies("~0.9.1", "0.9.0"));
+        assert!(!satisfies("~0.9.1", "0.10.0"));
+    }
+
+    #[test]
+    fn compound_range() {
+        assert!(satisfies(">=0.2, <0.4", "0.2.0"));
+        assert!(satisfies(">=0.2, <0.4", "0.3.9"));
+        assert!(!satisfies(">=0.2, <0.4", "0.4.0"));
+        assert!(!satisfies(">=0.2, <0.4", "0.1.9"));
+    }
+
+    #[test]
+    fn prerelease_orders_before_release() {
+        assert_eq!(compare("1.0.0-rc.1", "1.0.0"), Some(Ordering::Less));
+        assert_eq!(compare("1.0.0-alpha", "1.0.0-rc.1"), Some(Ordering::Less));
+        assert!(!at_least("18.0.0-rc.1", "18"));
+    }
+
+    #[test]
+    fn v_prefix() {
+        assert!(is_exact("v1.2.3"));
+        assert!(satisfies("v1.2.3", "1.2.3"));
+        assert!(satisfies("1.2.3", "v1.2.3"));
+        assert_eq!(compare("v1.2.3", "1.2.3"), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn short_versions_are_padded() {
+        assert_eq!(parse("18"), Some(Version::new(18, 0, 0)));
+        assert_eq!(parse("1.2"), Some(Version::new(1, 2, 0)));
+        assert_eq!(parse("1.2-beta"), Version::parse("1.2.0-beta").ok());
+        assert_eq!(parse("not-a-version"), None);
+        assert!(at_least("18.19.0", "18"));
+    }
+
+    #[test]
+    fn latest_and_unparsed_specs() {
+        assert!(satisfies("latest", "0.0.1"));
+        assert!(!is_exact("latest"));
+        assert!(satisfies("nightly", "nightly"));
+        assert!(!satisfies("nightly", "1.0.0"));
+    }
+
+    #[test]
+    fn ranges_exclude_prereleases() {
+        let req = VersionReq::parse("^0.3").unwrap();
+        let list = versions(&["0.3.1", "0.3.2", "0.4.0-rc.1", "0.3.3-beta.1"]);
+        assert_eq!(newest_matching(&req, &list), Some(&"0.3.2".to_string()));
+        assert!(!satisfies("^1.0", "1.1.0-rc.1"));
+    }
+
+    #[test]
+    fn ranges_naming_a_prerelease_include_it() {
+        let req = VersionReq::parse(">=1.0.0-rc.1").unwrap();
+        let list = versions(&["0.9.0", "1.0.0-rc.1", "1.0.0-rc.2"]);
+        assert_eq!(
+            newest_matching(&req, &list),
+            Some(&"1.0.0-rc.2".to_string())
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Shared bottles across uninstall: the saved manifest and its `source` survive, so the
+//! same URL installs again after upstream changes and `update` keeps following it.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn bottle(home: &Path, registry: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bottle"))
+        .args(args)
+        .arg("--allow-unsigned")
+        .env("HOME", home)
+        .env("BOTTLE_REGISTRY", format!("file://{}", registry.display()))
+        .output()
+        .expect("bottle runs")
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "bottle failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn write_manifest(path: &Path, version: &str) {
+    let manifest = format!(
+        r#"{{"name": "team", "version": "{}", "description": "Team stack", "tools": {{}}, "plugins": []}}"#,
+        version
+    );
+    fs::write(path, manifest).unwrap();
+}
+
+fn state(home: &Path) -> serde_json::Value {
+    let state = fs::read_to_string(home.join(".bottle/state.json")).unwrap();
+    serde_json::from_str(&state).unwrap()
+}
+
+#[test]
+fn uninstall_then_reinstall_by_url() {
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().join("home");
+    let registry = temp.path().join("registry");
+    let upstream = temp.path().join("upstream");
+    for dir in [&home, &registry, &upstream] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(registry.join("index.json"), r#"{"bottles": []}"#).unwrap();
+    let manifest = upstream.join("manifest.json");
+    write_manifest(&manifest, "1");
+    let url = format!("file://{}", manifest.display());
+
+    assert_success(&bottle(&home, &registry, &["install", &url, "-y"]));
+    assert_success(&bottle(&home, &registry, &["uninstall", "-y"]));
+
+    let saved = home.join(".bottle/bottles/team");
+    assert_eq!(
+        fs::read_to_string(saved.join("source")).unwrap().trim(),
+        url
+    );
+
+    // Upstream moved on: the same URL replaces the saved copy
+    write_manifest(&manifest, "2");
+    assert_success(&bottle(&home, &registry, &["install", &url, "-y"]));
+    assert_eq!(state(&home)["bottle_version"], "2");
+    assert_eq!(state(&home)["source"], url.as_str());
+
+    // Installed by name after an uninstall, it still updates from its source
+    assert_success(&bottle(&home, &registry, &["uninstall", "-y"]));
+    assert_success(&bottle(&home, &registry, &["install", "team", "-y"]));
+    assert_eq!(state(&home)["source"], url.as_str());
+
+    write_manifest(&manifest, "3");
+    assert_success(&bottle(&home, &registry, &["update", "-y"]));
+    assert_eq!(state(&home)["bottle_version"], "3");
+}